@@ -0,0 +1,587 @@
+//! In-memory [`Backend`] for tests.
+//!
+//! Objects are scripted with `add_*`/`update_*`/`remove_*`, each of which emits the matching
+//! subscription event, just like a real server would.
+//!
+//! ```
+//! use pulse_async::backend::{fake, Backend};
+//!
+//! let backend = fake::FakeBackend::new();
+//! backend.add_sink(fake::sink(0, "speakers"));
+//!
+//! futures::executor::block_on(async {
+//!     assert_eq!(backend.sink_list().await.unwrap().len(), 1);
+//! });
+//! ```
+
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use futures::{
+    channel::mpsc::UnboundedSender, future::LocalBoxFuture, stream::LocalBoxStream, FutureExt,
+    StreamExt,
+};
+use pulse::{
+    channelmap,
+    context::subscribe::{Facility, InterestMaskSet, Operation},
+    def, format,
+    proplist::{properties, Proplist},
+    sample,
+    time::MicroSeconds,
+    volume::{ChannelVolumes, Volume},
+};
+
+use super::Backend;
+use crate::{
+    volume::VolumeLevel, SinkInfo, SinkInputInfo, SourceInfo, SourceOutputInfo, SubscribeEvent,
+};
+
+#[derive(Default)]
+struct State {
+    cards: Vec<String>,
+    clients: Vec<String>,
+    sinks: BTreeMap<u32, SinkInfo>,
+    sources: BTreeMap<u32, SourceInfo>,
+    sink_inputs: BTreeMap<u32, SinkInputInfo>,
+    source_outputs: BTreeMap<u32, SourceOutputInfo>,
+    subscribers: Vec<(InterestMaskSet, UnboundedSender<SubscribeEvent>)>,
+}
+
+/// Scriptable in-memory server.
+///
+/// Clones share the same state, so a test can keep one to script events while the code under
+/// test owns another.
+#[derive(Clone, Default)]
+pub struct FakeBackend {
+    state: Rc<RefCell<State>>,
+}
+
+impl std::fmt::Debug for FakeBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FakeBackend").finish()
+    }
+}
+
+impl FakeBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends a subscription event to every subscriber interested in `facility`.
+    pub fn emit(&self, facility: Facility, operation: Operation, index: u32) {
+        let mut state = self.state.borrow_mut();
+
+        state.subscribers.retain(|(mask, tx)| {
+            if !mask.contains(facility.to_interest_mask()) {
+                return !tx.is_closed();
+            }
+
+            tx.unbounded_send(Ok((Some(facility), Some(operation), index)))
+                .is_ok()
+        });
+    }
+
+    /// Makes every active subscription yield an error.
+    pub fn fail_subscriptions(&self) {
+        for (_, tx) in self.state.borrow().subscribers.iter() {
+            tx.unbounded_send(Err(())).ok();
+        }
+    }
+
+    /// Adds a card, returns its index for the `card` field of sinks and sources.
+    pub fn add_card(&self, name: &str) -> u32 {
+        let index = {
+            let mut state = self.state.borrow_mut();
+            state.cards.push(name.to_string());
+            state.cards.len() as u32 - 1
+        };
+
+        self.emit(Facility::Card, Operation::New, index);
+        index
+    }
+
+    pub fn add_client(&self, name: &str) {
+        let index = {
+            let mut state = self.state.borrow_mut();
+            state.clients.push(name.to_string());
+            state.clients.len() as u32 - 1
+        };
+
+        self.emit(Facility::Client, Operation::New, index);
+    }
+
+    pub fn add_sink(&self, info: SinkInfo) {
+        let index = info.index;
+        self.state.borrow_mut().sinks.insert(index, info);
+        self.emit(Facility::Sink, Operation::New, index);
+    }
+
+    /// Edits a sink in place, returns `false` if it does not exist.
+    pub fn update_sink<F: FnOnce(&mut SinkInfo)>(&self, id: u32, f: F) -> bool {
+        match self.state.borrow_mut().sinks.get_mut(&id) {
+            Some(info) => f(info),
+            None => return false,
+        }
+
+        self.emit(Facility::Sink, Operation::Changed, id);
+        true
+    }
+
+    pub fn remove_sink(&self, id: u32) -> Option<SinkInfo> {
+        let info = self.state.borrow_mut().sinks.remove(&id)?;
+        self.emit(Facility::Sink, Operation::Removed, id);
+        Some(info)
+    }
+
+    pub fn add_source(&self, info: SourceInfo) {
+        let index = info.index;
+        self.state.borrow_mut().sources.insert(index, info);
+        self.emit(Facility::Source, Operation::New, index);
+    }
+
+    /// Edits a source in place, returns `false` if it does not exist.
+    pub fn update_source<F: FnOnce(&mut SourceInfo)>(&self, id: u32, f: F) -> bool {
+        match self.state.borrow_mut().sources.get_mut(&id) {
+            Some(info) => f(info),
+            None => return false,
+        }
+
+        self.emit(Facility::Source, Operation::Changed, id);
+        true
+    }
+
+    pub fn remove_source(&self, id: u32) -> Option<SourceInfo> {
+        let info = self.state.borrow_mut().sources.remove(&id)?;
+        self.emit(Facility::Source, Operation::Removed, id);
+        Some(info)
+    }
+
+    pub fn add_sink_input(&self, info: SinkInputInfo) {
+        let index = info.index;
+        self.state.borrow_mut().sink_inputs.insert(index, info);
+        self.emit(Facility::SinkInput, Operation::New, index);
+    }
+
+    /// Edits a sink input in place, returns `false` if it does not exist.
+    pub fn update_sink_input<F: FnOnce(&mut SinkInputInfo)>(&self, id: u32, f: F) -> bool {
+        match self.state.borrow_mut().sink_inputs.get_mut(&id) {
+            Some(info) => f(info),
+            None => return false,
+        }
+
+        self.emit(Facility::SinkInput, Operation::Changed, id);
+        true
+    }
+
+    pub fn remove_sink_input(&self, id: u32) -> Option<SinkInputInfo> {
+        let info = self.state.borrow_mut().sink_inputs.remove(&id)?;
+        self.emit(Facility::SinkInput, Operation::Removed, id);
+        Some(info)
+    }
+
+    pub fn add_source_output(&self, info: SourceOutputInfo) {
+        let index = info.index;
+        self.state.borrow_mut().source_outputs.insert(index, info);
+        self.emit(Facility::SourceOutput, Operation::New, index);
+    }
+
+    /// Edits a source output in place, returns `false` if it does not exist.
+    pub fn update_source_output<F: FnOnce(&mut SourceOutputInfo)>(&self, id: u32, f: F) -> bool {
+        match self.state.borrow_mut().source_outputs.get_mut(&id) {
+            Some(info) => f(info),
+            None => return false,
+        }
+
+        self.emit(Facility::SourceOutput, Operation::Changed, id);
+        true
+    }
+
+    pub fn remove_source_output(&self, id: u32) -> Option<SourceOutputInfo> {
+        let info = self.state.borrow_mut().source_outputs.remove(&id)?;
+        self.emit(Facility::SourceOutput, Operation::Removed, id);
+        Some(info)
+    }
+}
+
+/// Same conversion as [`Introspector::set_sink_volume`](crate::Introspector::set_sink_volume).
+fn percent_volume(channels: u8, v: f64) -> ChannelVolumes {
+    let mut vol = ChannelVolumes::default();
+
+    vol.set(channels.max(1), VolumeLevel::from_percent(v).volume());
+
+    vol
+}
+
+/// Whether `volume` has one entry per channel of `map`, servers reject it otherwise.
+fn fits(map: &channelmap::Map, volume: &ChannelVolumes) -> bool {
+    volume.len() == map.len()
+}
+
+fn not_found<T: 'static>() -> LocalBoxFuture<'static, Result<T, ()>> {
+    futures::future::ready(Err(())).boxed_local()
+}
+
+impl Backend for FakeBackend {
+    fn card_list(&self) -> LocalBoxFuture<'_, Result<Vec<String>, ()>> {
+        futures::future::ready(Ok(self.state.borrow().cards.clone())).boxed_local()
+    }
+
+    fn client_list(&self) -> LocalBoxFuture<'_, Result<Vec<String>, ()>> {
+        futures::future::ready(Ok(self.state.borrow().clients.clone())).boxed_local()
+    }
+
+    fn sink_list(&self) -> LocalBoxFuture<'_, Result<Vec<SinkInfo>, ()>> {
+        let list = self.state.borrow().sinks.values().cloned().collect();
+        futures::future::ready(Ok(list)).boxed_local()
+    }
+
+    fn sink(&self, id: u32) -> LocalBoxFuture<'_, Result<SinkInfo, ()>> {
+        match self.state.borrow().sinks.get(&id) {
+            Some(info) => futures::future::ready(Ok(info.clone())).boxed_local(),
+            None => not_found(),
+        }
+    }
+
+    fn set_sink_volume(&self, id: u32, v: f64) -> LocalBoxFuture<'_, bool> {
+        let success = self.update_sink(id, |info| {
+            info.volume = percent_volume(info.channel_map.len(), v);
+        });
+
+        futures::future::ready(success).boxed_local()
+    }
+
+    fn set_sink_volumes(&self, id: u32, volume: &ChannelVolumes) -> LocalBoxFuture<'_, bool> {
+        let valid = self
+            .state
+            .borrow()
+            .sinks
+            .get(&id)
+            .is_some_and(|info| fits(&info.channel_map, volume));
+        let success = valid && self.update_sink(id, |info| info.volume = *volume);
+
+        futures::future::ready(success).boxed_local()
+    }
+
+    fn source_list(&self) -> LocalBoxFuture<'_, Result<Vec<SourceInfo>, ()>> {
+        let list = self.state.borrow().sources.values().cloned().collect();
+        futures::future::ready(Ok(list)).boxed_local()
+    }
+
+    fn source(&self, id: u32) -> LocalBoxFuture<'_, Result<SourceInfo, ()>> {
+        match self.state.borrow().sources.get(&id) {
+            Some(info) => futures::future::ready(Ok(info.clone())).boxed_local(),
+            None => not_found(),
+        }
+    }
+
+    fn set_source_volume(&self, id: u32, v: f64) -> LocalBoxFuture<'_, bool> {
+        let success = self.update_source(id, |info| {
+            info.volume = percent_volume(info.channel_map.len(), v);
+        });
+
+        futures::future::ready(success).boxed_local()
+    }
+
+    fn set_source_volumes(&self, id: u32, volume: &ChannelVolumes) -> LocalBoxFuture<'_, bool> {
+        let valid = self
+            .state
+            .borrow()
+            .sources
+            .get(&id)
+            .is_some_and(|info| fits(&info.channel_map, volume));
+        let success = valid && self.update_source(id, |info| info.volume = *volume);
+
+        futures::future::ready(success).boxed_local()
+    }
+
+    fn sink_input_list(&self) -> LocalBoxFuture<'_, Result<Vec<SinkInputInfo>, ()>> {
+        let list = self.state.borrow().sink_inputs.values().cloned().collect();
+        futures::future::ready(Ok(list)).boxed_local()
+    }
+
+    fn sink_input(&self, id: u32) -> LocalBoxFuture<'_, Result<SinkInputInfo, ()>> {
+        match self.state.borrow().sink_inputs.get(&id) {
+            Some(info) => futures::future::ready(Ok(info.clone())).boxed_local(),
+            None => not_found(),
+        }
+    }
+
+    fn set_sink_input_volume(&self, id: u32, v: f64) -> LocalBoxFuture<'_, bool> {
+        let success = self.update_sink_input(id, |info| {
+            info.volume = percent_volume(info.channel_map.len(), v);
+        });
+
+        futures::future::ready(success).boxed_local()
+    }
+
+    fn set_sink_input_volumes(&self, id: u32, volume: &ChannelVolumes) -> LocalBoxFuture<'_, bool> {
+        let valid = self
+            .state
+            .borrow()
+            .sink_inputs
+            .get(&id)
+            .is_some_and(|info| fits(&info.channel_map, volume));
+        let success = valid && self.update_sink_input(id, |info| info.volume = *volume);
+
+        futures::future::ready(success).boxed_local()
+    }
+
+    fn source_output_list(&self) -> LocalBoxFuture<'_, Result<Vec<SourceOutputInfo>, ()>> {
+        let list = self
+            .state
+            .borrow()
+            .source_outputs
+            .values()
+            .cloned()
+            .collect();
+        futures::future::ready(Ok(list)).boxed_local()
+    }
+
+    fn source_output(&self, id: u32) -> LocalBoxFuture<'_, Result<SourceOutputInfo, ()>> {
+        match self.state.borrow().source_outputs.get(&id) {
+            Some(info) => futures::future::ready(Ok(info.clone())).boxed_local(),
+            None => not_found(),
+        }
+    }
+
+    fn set_source_output_volume(&self, id: u32, v: f64) -> LocalBoxFuture<'_, bool> {
+        let success = self.update_source_output(id, |info| {
+            info.volume = percent_volume(info.channel_map.len(), v);
+        });
+
+        futures::future::ready(success).boxed_local()
+    }
+
+    fn set_source_output_volumes(
+        &self,
+        id: u32,
+        volume: &ChannelVolumes,
+    ) -> LocalBoxFuture<'_, bool> {
+        let valid = self
+            .state
+            .borrow()
+            .source_outputs
+            .get(&id)
+            .is_some_and(|info| fits(&info.channel_map, volume));
+        let success = valid && self.update_source_output(id, |info| info.volume = *volume);
+
+        futures::future::ready(success).boxed_local()
+    }
+
+    fn subscribe(&self, mask: InterestMaskSet) -> LocalBoxStream<'static, SubscribeEvent> {
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        self.state.borrow_mut().subscribers.push((mask, tx));
+
+        rx.boxed_local()
+    }
+}
+
+fn stereo() -> (sample::Spec, channelmap::Map, ChannelVolumes) {
+    let spec = sample::Spec {
+        format: sample::Format::F32le,
+        rate: 48000,
+        channels: 2,
+    };
+
+    let mut map = channelmap::Map::default();
+    map.init_stereo();
+
+    let mut volume = ChannelVolumes::default();
+    volume.set(2, Volume::NORMAL);
+
+    (spec, map, volume)
+}
+
+/// Creates a stereo sink at 100% volume.
+pub fn sink(index: u32, name: &str) -> SinkInfo {
+    let (sample_spec, channel_map, volume) = stereo();
+
+    SinkInfo {
+        name: Some(name.to_string()),
+        index,
+        description: Some(name.to_string()),
+        sample_spec,
+        channel_map,
+        owner_module: None,
+        volume,
+        mute: false,
+        monitor_source: index,
+        monitor_source_name: Some(format!("{}.monitor", name)),
+        latency: MicroSeconds(0),
+        driver: None,
+        flags: def::SinkFlagSet::empty(),
+        proplist: Proplist::new().unwrap(),
+        configured_latency: MicroSeconds(0),
+        base_volume: Volume::NORMAL,
+        state: def::SinkState::Idle,
+        n_volume_steps: 0,
+        card: None,
+        ports: Vec::new(),
+        active_port: None,
+        formats: Vec::new(),
+    }
+}
+
+/// Creates a stereo source at 100% volume.
+pub fn source(index: u32, name: &str) -> SourceInfo {
+    let (sample_spec, channel_map, volume) = stereo();
+
+    SourceInfo {
+        name: Some(name.to_string()),
+        index,
+        description: Some(name.to_string()),
+        sample_spec,
+        channel_map,
+        owner_module: None,
+        volume,
+        mute: false,
+        monitor_of_sink: None,
+        monitor_of_sink_name: None,
+        latency: MicroSeconds(0),
+        driver: None,
+        flags: def::SourceFlagSet::empty(),
+        proplist: Proplist::new().unwrap(),
+        configured_latency: MicroSeconds(0),
+        base_volume: Volume::NORMAL,
+        state: def::SourceState::Idle,
+        n_volume_steps: 0,
+        card: None,
+        ports: Vec::new(),
+        active_port: None,
+        formats: Vec::new(),
+    }
+}
+
+/// Creates a stereo stream of application `app_name` playing on `sink`.
+pub fn sink_input(index: u32, sink: u32, app_name: &str) -> SinkInputInfo {
+    let (sample_spec, channel_map, volume) = stereo();
+
+    let mut proplist = Proplist::new().unwrap();
+    proplist
+        .set_str(properties::APPLICATION_NAME, app_name)
+        .unwrap();
+
+    SinkInputInfo {
+        index,
+        name: Some("Playback".to_string()),
+        owner_module: None,
+        client: None,
+        sink,
+        sample_spec,
+        channel_map,
+        volume,
+        buffer_usec: MicroSeconds(0),
+        sink_usec: MicroSeconds(0),
+        resample_method: None,
+        driver: None,
+        mute: false,
+        proplist,
+        corked: false,
+        has_volume: true,
+        volume_writable: true,
+        format: format::Info::new().unwrap(),
+    }
+}
+
+/// Creates a stereo stream of application `app_name` recording from `source`.
+pub fn source_output(index: u32, source: u32, app_name: &str) -> SourceOutputInfo {
+    let (sample_spec, channel_map, volume) = stereo();
+
+    let mut proplist = Proplist::new().unwrap();
+    proplist
+        .set_str(properties::APPLICATION_NAME, app_name)
+        .unwrap();
+
+    SourceOutputInfo {
+        index,
+        name: Some("Record".to_string()),
+        owner_module: None,
+        client: None,
+        source,
+        sample_spec,
+        channel_map,
+        buffer_usec: MicroSeconds(0),
+        source_usec: MicroSeconds(0),
+        resample_method: None,
+        driver: None,
+        proplist,
+        corked: false,
+        volume,
+        mute: false,
+        has_volume: true,
+        volume_writable: true,
+        format: format::Info::new().unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists() {
+        let backend = FakeBackend::new();
+        backend.add_card("card");
+        backend.add_sink(sink(0, "speakers"));
+        backend.add_source(source(1, "mic"));
+        backend.add_sink_input(sink_input(7, 0, "Player"));
+        backend.add_source_output(source_output(8, 1, "Recorder"));
+
+        futures::executor::block_on(async move {
+            assert_eq!(backend.card_list().await.unwrap(), vec!["card".to_string()]);
+            assert_eq!(backend.sink_list().await.unwrap()[0].index, 0);
+            assert_eq!(backend.source_list().await.unwrap()[0].index, 1);
+
+            let input = backend.sink_input(7).await.unwrap();
+            assert_eq!(
+                input.proplist.get_str(properties::APPLICATION_NAME),
+                Some("Player".to_string())
+            );
+
+            assert!(backend.sink_input(199).await.is_err());
+
+            let output = backend.source_output(8).await.unwrap();
+            assert_eq!(output.source, 1);
+            assert_eq!(backend.source_output_list().await.unwrap().len(), 1);
+        });
+    }
+
+    #[test]
+    fn volume() {
+        let backend = FakeBackend::new();
+        backend.add_sink(sink(0, "speakers"));
+        backend.add_source(source(1, "mic"));
+
+        futures::executor::block_on(async move {
+            assert!(backend.set_sink_volume(0, 50.0).await);
+            assert!(!backend.set_sink_volume(1, 50.0).await);
+
+            let info = backend.sink(0).await.unwrap();
+            assert_eq!(info.volume.len(), 2);
+            assert_eq!(info.volume.max().0, Volume::NORMAL.0 / 2);
+
+            assert!(backend.set_source_volume(1, 25.0).await);
+            let info = backend.source(1).await.unwrap();
+            assert_eq!(info.volume.max().0, Volume::NORMAL.0 / 4);
+        });
+    }
+
+    #[test]
+    fn events() {
+        let backend = FakeBackend::new();
+        let mut events = backend.subscribe(InterestMaskSet::SINK_INPUT);
+
+        backend.add_sink(sink(0, "speakers"));
+        backend.add_sink_input(sink_input(3, 0, "Player"));
+        backend.update_sink_input(3, |info| info.mute = true);
+        backend.remove_sink_input(3);
+
+        futures::executor::block_on(async move {
+            let expected = [Operation::New, Operation::Changed, Operation::Removed];
+
+            for op in expected {
+                let event = events.next().await.unwrap().unwrap();
+                assert_eq!(event, (Some(Facility::SinkInput), Some(op), 3));
+            }
+        });
+    }
+}
@@ -0,0 +1,165 @@
+//! Backend abstraction over the parts of [`Context`] applications query and control.
+//!
+//! Code written against [`Backend`] instead of [`Context`] can be driven by [`fake::FakeBackend`]
+//! in tests, without a sound server.
+
+use futures::{future::LocalBoxFuture, stream::LocalBoxStream, FutureExt, StreamExt};
+use pulse::{context::subscribe::InterestMaskSet, volume::ChannelVolumes};
+
+use crate::{Context, SinkInfo, SinkInputInfo, SourceInfo, SourceOutputInfo, SubscribeEvent};
+
+pub mod fake;
+
+pub trait Backend {
+    /// Gets the card list.
+    fn card_list(&self) -> LocalBoxFuture<'_, Result<Vec<String>, ()>>;
+
+    /// Gets the client list.
+    fn client_list(&self) -> LocalBoxFuture<'_, Result<Vec<String>, ()>>;
+
+    /// Gets the sink list.
+    fn sink_list(&self) -> LocalBoxFuture<'_, Result<Vec<SinkInfo>, ()>>;
+
+    /// Gets information about a sink by its index.
+    fn sink(&self, id: u32) -> LocalBoxFuture<'_, Result<SinkInfo, ()>>;
+
+    /// Sets the volume of a sink, resolves with `true` on success.
+    fn set_sink_volume(&self, id: u32, v: f64) -> LocalBoxFuture<'_, bool>;
+
+    /// Sets the volume of every channel of a sink, resolves with `true` on success.
+    fn set_sink_volumes(&self, id: u32, volume: &ChannelVolumes) -> LocalBoxFuture<'_, bool>;
+
+    /// Gets the source list.
+    fn source_list(&self) -> LocalBoxFuture<'_, Result<Vec<SourceInfo>, ()>>;
+
+    /// Gets information about a source by its index.
+    fn source(&self, id: u32) -> LocalBoxFuture<'_, Result<SourceInfo, ()>>;
+
+    /// Sets the volume of a source, resolves with `true` on success.
+    fn set_source_volume(&self, id: u32, v: f64) -> LocalBoxFuture<'_, bool>;
+
+    /// Sets the volume of every channel of a source, resolves with `true` on success.
+    fn set_source_volumes(&self, id: u32, volume: &ChannelVolumes) -> LocalBoxFuture<'_, bool>;
+
+    /// Gets the sink input list.
+    fn sink_input_list(&self) -> LocalBoxFuture<'_, Result<Vec<SinkInputInfo>, ()>>;
+
+    /// Gets some information about a sink input by its index.
+    fn sink_input(&self, id: u32) -> LocalBoxFuture<'_, Result<SinkInputInfo, ()>>;
+
+    /// Sets the volume of a sink input stream, resolves with `true` on success.
+    fn set_sink_input_volume(&self, id: u32, v: f64) -> LocalBoxFuture<'_, bool>;
+
+    /// Sets the volume of every channel of a sink input stream, resolves with `true` on success.
+    fn set_sink_input_volumes(&self, id: u32, volume: &ChannelVolumes) -> LocalBoxFuture<'_, bool>;
+
+    /// Gets the source output list.
+    fn source_output_list(&self) -> LocalBoxFuture<'_, Result<Vec<SourceOutputInfo>, ()>>;
+
+    /// Gets some information about a source output by its index.
+    fn source_output(&self, id: u32) -> LocalBoxFuture<'_, Result<SourceOutputInfo, ()>>;
+
+    /// Sets the volume of a source output stream, resolves with `true` on success.
+    fn set_source_output_volume(&self, id: u32, v: f64) -> LocalBoxFuture<'_, bool>;
+
+    /// Sets the volume of every channel of a source output stream, resolves with `true` on success.
+    fn set_source_output_volumes(
+        &self,
+        id: u32,
+        volume: &ChannelVolumes,
+    ) -> LocalBoxFuture<'_, bool>;
+
+    /// Subscribes to server events matching `mask`.
+    fn subscribe(&self, mask: InterestMaskSet) -> LocalBoxStream<'static, SubscribeEvent>;
+}
+
+impl Backend for Context {
+    fn card_list(&self) -> LocalBoxFuture<'_, Result<Vec<String>, ()>> {
+        async move { self.introspect().card_list().await }.boxed_local()
+    }
+
+    fn client_list(&self) -> LocalBoxFuture<'_, Result<Vec<String>, ()>> {
+        async move { self.introspect().client_list().await }.boxed_local()
+    }
+
+    fn sink_list(&self) -> LocalBoxFuture<'_, Result<Vec<SinkInfo>, ()>> {
+        async move { self.introspect().sink_list().await }.boxed_local()
+    }
+
+    fn sink(&self, id: u32) -> LocalBoxFuture<'_, Result<SinkInfo, ()>> {
+        async move { self.introspect().sink(id).await }.boxed_local()
+    }
+
+    fn set_sink_volume(&self, id: u32, v: f64) -> LocalBoxFuture<'_, bool> {
+        async move { self.introspect().set_sink_volume(id, v).await }.boxed_local()
+    }
+
+    fn set_sink_volumes(&self, id: u32, volume: &ChannelVolumes) -> LocalBoxFuture<'_, bool> {
+        let volume = *volume;
+        async move { self.introspect().set_sink_volumes(id, &volume).await }.boxed_local()
+    }
+
+    fn source_list(&self) -> LocalBoxFuture<'_, Result<Vec<SourceInfo>, ()>> {
+        async move { self.introspect().source_list().await }.boxed_local()
+    }
+
+    fn source(&self, id: u32) -> LocalBoxFuture<'_, Result<SourceInfo, ()>> {
+        async move { self.introspect().source(id).await }.boxed_local()
+    }
+
+    fn set_source_volume(&self, id: u32, v: f64) -> LocalBoxFuture<'_, bool> {
+        async move { self.introspect().set_source_volume(id, v).await }.boxed_local()
+    }
+
+    fn set_source_volumes(&self, id: u32, volume: &ChannelVolumes) -> LocalBoxFuture<'_, bool> {
+        let volume = *volume;
+        async move { self.introspect().set_source_volumes(id, &volume).await }.boxed_local()
+    }
+
+    fn sink_input_list(&self) -> LocalBoxFuture<'_, Result<Vec<SinkInputInfo>, ()>> {
+        async move { self.introspect().sink_input_list().await }.boxed_local()
+    }
+
+    fn sink_input(&self, id: u32) -> LocalBoxFuture<'_, Result<SinkInputInfo, ()>> {
+        async move { self.introspect().sink_input(id).await }.boxed_local()
+    }
+
+    fn set_sink_input_volume(&self, id: u32, v: f64) -> LocalBoxFuture<'_, bool> {
+        async move { self.introspect().set_sink_input_volume(id, v).await }.boxed_local()
+    }
+
+    fn set_sink_input_volumes(&self, id: u32, volume: &ChannelVolumes) -> LocalBoxFuture<'_, bool> {
+        let volume = *volume;
+        async move { self.introspect().set_sink_input_volumes(id, &volume).await }.boxed_local()
+    }
+
+    fn source_output_list(&self) -> LocalBoxFuture<'_, Result<Vec<SourceOutputInfo>, ()>> {
+        async move { self.introspect().source_output_list().await }.boxed_local()
+    }
+
+    fn source_output(&self, id: u32) -> LocalBoxFuture<'_, Result<SourceOutputInfo, ()>> {
+        async move { self.introspect().source_output(id).await }.boxed_local()
+    }
+
+    fn set_source_output_volume(&self, id: u32, v: f64) -> LocalBoxFuture<'_, bool> {
+        async move { self.introspect().set_source_output_volume(id, v).await }.boxed_local()
+    }
+
+    fn set_source_output_volumes(
+        &self,
+        id: u32,
+        volume: &ChannelVolumes,
+    ) -> LocalBoxFuture<'_, bool> {
+        let volume = *volume;
+        async move {
+            self.introspect()
+                .set_source_output_volumes(id, &volume)
+                .await
+        }
+        .boxed_local()
+    }
+
+    fn subscribe(&self, mask: InterestMaskSet) -> LocalBoxStream<'static, SubscribeEvent> {
+        Context::subscribe(self, mask).boxed_local()
+    }
+}
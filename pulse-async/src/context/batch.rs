@@ -0,0 +1,264 @@
+//! Sending many independent operations at once, e.g. to apply a preset.
+
+use futures::channel::oneshot;
+use pulse::{operation::Operation, volume::ChannelVolumes};
+
+use super::Context;
+
+/// One change of a [`Batch`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BatchOp {
+    SinkVolume {
+        index: u32,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::channel_volumes"))]
+        volume: ChannelVolumes,
+    },
+    SinkMute {
+        index: u32,
+        mute: bool,
+    },
+    SourceVolume {
+        index: u32,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::channel_volumes"))]
+        volume: ChannelVolumes,
+    },
+    SourceMute {
+        index: u32,
+        mute: bool,
+    },
+    SinkInputVolume {
+        index: u32,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::channel_volumes"))]
+        volume: ChannelVolumes,
+    },
+    SinkInputMute {
+        index: u32,
+        mute: bool,
+    },
+    /// Moves a sink input to another sink.
+    MoveSinkInput {
+        index: u32,
+        sink: u32,
+    },
+    SourceOutputVolume {
+        index: u32,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::channel_volumes"))]
+        volume: ChannelVolumes,
+    },
+    SourceOutputMute {
+        index: u32,
+        mute: bool,
+    },
+    /// Moves a source output to another source.
+    MoveSourceOutput {
+        index: u32,
+        source: u32,
+    },
+    /// Makes the sink named `name` the default one.
+    DefaultSink {
+        name: String,
+    },
+    /// Makes the source named `name` the default one.
+    DefaultSource {
+        name: String,
+    },
+}
+
+/// Collects operations to send them to the server all at once, see [`Context::batch`].
+///
+/// The server applies every operation on its own, so a batch is not atomic: some operations may
+/// succeed while others fail, which [`BatchResults`] tells apart.
+#[derive(Debug)]
+pub struct Batch<'a> {
+    context: &'a Context,
+    ops: Vec<BatchOp>,
+}
+
+/// Outcome of every operation of a [`Batch`], in the order they were pushed.
+#[derive(Debug, Clone)]
+pub struct BatchResults {
+    pub ops: Vec<BatchOp>,
+    pub results: Vec<bool>,
+}
+
+impl BatchResults {
+    /// Whether every operation succeeded.
+    pub fn is_ok(&self) -> bool {
+        self.results.iter().all(|success| *success)
+    }
+
+    /// Operations that failed or timed out.
+    pub fn failed(&self) -> impl Iterator<Item = &BatchOp> {
+        self.ops
+            .iter()
+            .zip(&self.results)
+            .filter(|(_, success)| !**success)
+            .map(|(op, _)| op)
+    }
+}
+
+type Pending = (Operation<dyn FnMut(bool)>, oneshot::Receiver<bool>);
+
+impl<'a> Batch<'a> {
+    pub(crate) fn new(context: &'a Context) -> Self {
+        Self {
+            context,
+            ops: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, op: BatchOp) -> &mut Self {
+        self.ops.push(op);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Sends every operation without waiting for the server in between, then waits for all of
+    /// their results.
+    ///
+    /// Each operation is subject to [`Context::operation_timeout`].
+    pub async fn run(self) -> BatchResults {
+        let pending: Vec<Pending> = {
+            let _lock = self.context.lock();
+            let mut inner = self.context.inner.borrow_mut();
+            let mut introspector = inner.context.introspect();
+
+            self.ops
+                .iter()
+                .map(|op| {
+                    let (tx, rx) = oneshot::channel::<bool>();
+
+                    let mut tx = Some(tx);
+                    let callback = move |success| {
+                        if let Some(tx) = tx.take() {
+                            tx.send(success).ok();
+                        }
+                    };
+
+                    let operation = match op {
+                        BatchOp::SinkVolume { index, volume } => introspector
+                            .set_sink_volume_by_index(*index, volume, Some(Box::new(callback))),
+                        BatchOp::SinkMute { index, mute } => introspector.set_sink_mute_by_index(
+                            *index,
+                            *mute,
+                            Some(Box::new(callback)),
+                        ),
+                        BatchOp::SourceVolume { index, volume } => introspector
+                            .set_source_volume_by_index(*index, volume, Some(Box::new(callback))),
+                        BatchOp::SourceMute { index, mute } => introspector
+                            .set_source_mute_by_index(*index, *mute, Some(Box::new(callback))),
+                        BatchOp::SinkInputVolume { index, volume } => introspector
+                            .set_sink_input_volume(*index, volume, Some(Box::new(callback))),
+                        BatchOp::SinkInputMute { index, mute } => introspector.set_sink_input_mute(
+                            *index,
+                            *mute,
+                            Some(Box::new(callback)),
+                        ),
+                        BatchOp::MoveSinkInput { index, sink } => introspector
+                            .move_sink_input_by_index(*index, *sink, Some(Box::new(callback))),
+                        BatchOp::SourceOutputVolume { index, volume } => introspector
+                            .set_source_output_volume(*index, volume, Some(Box::new(callback))),
+                        BatchOp::SourceOutputMute { index, mute } => introspector
+                            .set_source_output_mute(*index, *mute, Some(Box::new(callback))),
+                        BatchOp::MoveSourceOutput { index, source } => introspector
+                            .move_source_output_by_index(*index, *source, Some(Box::new(callback))),
+                        BatchOp::DefaultSink { name } => {
+                            inner.context.set_default_sink(name, callback)
+                        }
+                        BatchOp::DefaultSource { name } => {
+                            inner.context.set_default_source(name, callback)
+                        }
+                    };
+
+                    (operation, rx)
+                })
+                .collect()
+        };
+
+        let timeout = self.context.operation_timeout();
+        let results = futures::future::join_all(
+            pending
+                .into_iter()
+                .map(|(operation, rx)| self.context.wait(operation, rx, timeout)),
+        )
+        .await;
+
+        BatchResults {
+            ops: self.ops,
+            results: results
+                .into_iter()
+                .map(|success| success.unwrap_or(false))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pulse::volume::{ChannelVolumes, Volume};
+
+    use super::BatchOp;
+    use crate::test_server::TestServer;
+
+    #[test]
+    fn results() {
+        let server = TestServer::spawn();
+
+        futures::executor::block_on(async {
+            let context = server.connect().await;
+            let sinks = context.introspect().sink_list().await.unwrap();
+
+            let mut volume = ChannelVolumes::default();
+            volume.set(sinks[0].channel_map.len(), Volume(Volume::NORMAL.0 / 2));
+
+            let mut batch = context.batch();
+            batch
+                .push(BatchOp::SinkVolume {
+                    index: sinks[0].index,
+                    volume,
+                })
+                .push(BatchOp::SinkMute {
+                    index: sinks[1].index,
+                    mute: true,
+                })
+                .push(BatchOp::SinkMute {
+                    index: u32::MAX - 1,
+                    mute: true,
+                })
+                .push(BatchOp::DefaultSink {
+                    name: sinks[1].name.clone().unwrap(),
+                });
+            assert_eq!(batch.len(), 4);
+
+            let results = batch.run().await;
+            assert_eq!(results.results, [true, true, false, true]);
+            assert!(!results.is_ok());
+            assert!(matches!(
+                results.failed().collect::<Vec<_>>()[..],
+                [BatchOp::SinkMute { index, .. }] if *index == u32::MAX - 1
+            ));
+
+            let sink = context.introspect().sink(sinks[0].index).await.unwrap();
+            assert_eq!(sink.volume.max(), Volume(Volume::NORMAL.0 / 2));
+            assert!(
+                context
+                    .introspect()
+                    .sink(sinks[1].index)
+                    .await
+                    .unwrap()
+                    .mute
+            );
+
+            let info = context.introspect().server_info().await.unwrap();
+            assert_eq!(info.default_sink_name, sinks[1].name);
+        });
+    }
+}
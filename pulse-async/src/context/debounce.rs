@@ -0,0 +1,191 @@
+//! Coalescing bursts of subscription events, see [`Context::subscribe_debounced`].
+
+use std::{collections::VecDeque, time::Duration};
+
+use futures::{
+    future::{self, Either},
+    Stream, StreamExt,
+};
+use pulse::context::subscribe::Operation;
+
+use super::{Context, SubscribeEvent};
+
+/// Adds `event` to `pending`, merging it with the previous event of the same object.
+///
+/// Changes of an object that is new or removed anyway are dropped, and an object that is both
+/// created and removed disappears altogether.
+fn coalesce(pending: &mut Vec<SubscribeEvent>, event: SubscribeEvent) {
+    let (facility, operation, index) = match event {
+        Ok((Some(facility), Some(operation), index)) => (facility, operation, index),
+        event => {
+            pending.push(event);
+            return;
+        }
+    };
+
+    let last = pending
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(i, other)| match other {
+            Ok((Some(other_facility), Some(previous), other_index))
+                if *other_facility == facility && *other_index == index =>
+            {
+                Some((i, *previous))
+            }
+            _ => None,
+        });
+
+    let (last, previous) = match last {
+        Some(last) => last,
+        None => {
+            pending.push(event);
+            return;
+        }
+    };
+
+    match (previous, operation) {
+        (Operation::New, Operation::Removed) => {
+            let _ = pending.remove(last);
+        }
+        (Operation::Changed, Operation::Removed) => pending[last] = event,
+        // The index got reused, the new object still has to be reported
+        (_, Operation::New) => pending.push(event),
+        _ => {}
+    }
+}
+
+struct State<S> {
+    context: Context,
+    events: S,
+    window: Duration,
+    ready: VecDeque<SubscribeEvent>,
+    done: bool,
+}
+
+/// Holds back the events of `events` for `window` after the first one of a burst and yields them
+/// coalesced.
+pub(crate) fn debounce<S>(
+    context: Context,
+    events: S,
+    window: Duration,
+) -> impl Stream<Item = SubscribeEvent> + Unpin
+where
+    S: Stream<Item = SubscribeEvent> + Unpin + 'static,
+{
+    let state = State {
+        context,
+        events,
+        window,
+        ready: VecDeque::new(),
+        done: false,
+    };
+
+    Box::pin(futures::stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(event) = state.ready.pop_front() {
+                return Some((event, state));
+            }
+
+            if state.done {
+                return None;
+            }
+
+            let first = match state.events.next().await {
+                Some(event) => event,
+                None => return None,
+            };
+
+            let mut pending = vec![first];
+
+            let sleep = state.context.sleep(state.window);
+            futures::pin_mut!(sleep);
+
+            loop {
+                match future::select(state.events.next(), &mut sleep).await {
+                    Either::Left((Some(event), _)) => coalesce(&mut pending, event),
+                    Either::Left((None, _)) => {
+                        state.done = true;
+                        break;
+                    }
+                    Either::Right(_) => break,
+                }
+            }
+
+            state.ready.extend(pending);
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use pulse::context::subscribe::{Facility, Operation};
+
+    use super::coalesce;
+    use crate::context::SubscribeEvent;
+
+    fn run(events: &[(Facility, Operation, u32)]) -> Vec<SubscribeEvent> {
+        let mut pending = Vec::new();
+
+        for (facility, operation, index) in events {
+            coalesce(
+                &mut pending,
+                Ok((Some(*facility), Some(*operation), *index)),
+            );
+        }
+
+        pending
+    }
+
+    fn event(facility: Facility, operation: Operation, index: u32) -> SubscribeEvent {
+        Ok((Some(facility), Some(operation), index))
+    }
+
+    #[test]
+    fn coalesces() {
+        use Facility::{Sink, SinkInput};
+        use Operation::{Changed, New, Removed};
+
+        assert_eq!(
+            run(&[
+                (SinkInput, Changed, 1),
+                (Sink, Changed, 1),
+                (SinkInput, Changed, 1)
+            ]),
+            [event(SinkInput, Changed, 1), event(Sink, Changed, 1)]
+        );
+
+        assert_eq!(
+            run(&[
+                (SinkInput, New, 2),
+                (SinkInput, Changed, 2),
+                (SinkInput, Changed, 3)
+            ]),
+            [event(SinkInput, New, 2), event(SinkInput, Changed, 3)]
+        );
+
+        assert!(run(&[
+            (SinkInput, New, 4),
+            (SinkInput, Changed, 4),
+            (SinkInput, Removed, 4)
+        ])
+        .is_empty());
+
+        assert_eq!(
+            run(&[
+                (SinkInput, Changed, 5),
+                (SinkInput, Removed, 5),
+                (SinkInput, New, 5)
+            ]),
+            [event(SinkInput, Removed, 5), event(SinkInput, New, 5)]
+        );
+    }
+
+    #[test]
+    fn keeps_errors() {
+        let mut pending = vec![Err(())];
+        coalesce(&mut pending, Err(()));
+
+        assert_eq!(pending, [Err(()), Err(())]);
+    }
+}
@@ -0,0 +1,291 @@
+//! Access to `module-device-manager`, which remembers every device ever seen and routes new
+//! streams to the highest priority device available for their `media.role`.
+//!
+//! Devices are named `sink:<sink name>` or `source:<source name>`.
+
+use futures::{channel::mpsc::UnboundedReceiver, StreamExt};
+use pulse::context::ext_device_manager::{self, Info};
+
+use super::{introspector::list_callback, Context};
+
+/// Roles module-device-manager keeps a device priority list for.
+pub const ROLES: &[&str] = &[
+    "none",
+    "video",
+    "music",
+    "game",
+    "event",
+    "phone",
+    "animation",
+    "production",
+    "a11y",
+];
+
+/// Priority of a device for one role, lower is preferred.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RolePriority {
+    pub role: String,
+    pub priority: u32,
+}
+
+/// A device known to module-device-manager.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceManagerEntry {
+    /// `sink:<name>` or `source:<name>`.
+    pub name: String,
+    /// Description, kept around while the device is unplugged.
+    pub description: Option<String>,
+    /// Icon name of the device.
+    pub icon: Option<String>,
+    /// Index of the sink or source, `None` while the device is unavailable.
+    pub index: Option<u32>,
+    pub role_priorities: Vec<RolePriority>,
+}
+
+impl<'a> From<&'a Info<'a>> for DeviceManagerEntry {
+    fn from(item: &'a Info<'a>) -> Self {
+        Self {
+            name: item
+                .name
+                .as_ref()
+                .map(|cow| cow.to_string())
+                .unwrap_or_default(),
+            description: item.description.as_ref().map(|cow| cow.to_string()),
+            icon: item.icon.as_ref().map(|cow| cow.to_string()),
+            index: item.index,
+            role_priorities: item
+                .role_priorities
+                .iter()
+                .map(|role| RolePriority {
+                    role: role
+                        .role
+                        .as_ref()
+                        .map(|cow| cow.to_string())
+                        .unwrap_or_default(),
+                    priority: role.priority,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl DeviceManagerEntry {
+    pub fn is_sink(&self) -> bool {
+        self.name.starts_with("sink:")
+    }
+
+    /// Name of the sink or source, without the `sink:`/`source:` prefix.
+    pub fn device_name(&self) -> &str {
+        self.name
+            .split_once(':')
+            .map_or(self.name.as_str(), |(_, name)| name)
+    }
+
+    /// Priority for `role`, lower is preferred.
+    pub fn priority(&self, role: &str) -> Option<u32> {
+        self.role_priorities
+            .iter()
+            .find(|priority| priority.role == role)
+            .map(|priority| priority.priority)
+    }
+}
+
+pub struct DeviceManager<'a> {
+    ext: ext_device_manager::DeviceManager,
+    context: &'a Context,
+}
+
+macro_rules! success_callback {
+    ($tx:ident) => {{
+        let mut tx = Some($tx);
+
+        move |success| {
+            if let Some(tx) = tx.take() {
+                tx.send(success).ok();
+            }
+        }
+    }};
+}
+
+impl<'a> DeviceManager<'a> {
+    pub(crate) fn new(context: &'a Context) -> Self {
+        let _lock = context.lock();
+        let ext = context.inner.borrow().context.device_manager();
+
+        Self { ext, context }
+    }
+
+    /// Gets all known devices, including unplugged ones.
+    pub async fn read(&mut self) -> Result<Vec<DeviceManagerEntry>, ()> {
+        let (tx, rx) = futures::channel::oneshot::channel();
+
+        let operation = {
+            let _lock = self.context.lock();
+            self.ext.read(list_callback!(tx, DeviceManagerEntry::from))
+        };
+
+        self.context
+            .wait(operation, rx, self.context.operation_timeout())
+            .await
+            .unwrap_or(Err(()))
+    }
+
+    /// Renames a device, `device` being `sink:<name>` or `source:<name>`.
+    pub async fn set_device_description(&mut self, device: &str, description: &str) -> bool {
+        let (tx, rx) = futures::channel::oneshot::channel::<bool>();
+
+        let operation = {
+            let _lock = self.context.lock();
+            self.ext
+                .set_device_description(device, description, success_callback!(tx))
+        };
+
+        self.context
+            .wait(operation, rx, self.context.operation_timeout())
+            .await
+            .unwrap_or(false)
+    }
+
+    /// Forgets devices, they are added again the next time they show up.
+    pub async fn delete(&mut self, devices: &[&str]) -> bool {
+        let (tx, rx) = futures::channel::oneshot::channel::<bool>();
+
+        let operation = {
+            let _lock = self.context.lock();
+            self.ext.delete(devices, success_callback!(tx))
+        };
+
+        self.context
+            .wait(operation, rx, self.context.operation_timeout())
+            .await
+            .unwrap_or(false)
+    }
+
+    /// Whether new streams are routed by the role priority lists.
+    pub async fn enable_role_device_priority_routing(&mut self, enable: bool) -> bool {
+        let (tx, rx) = futures::channel::oneshot::channel::<bool>();
+
+        let operation = {
+            let _lock = self.context.lock();
+            self.ext
+                .enable_role_device_priority_routing(enable, success_callback!(tx))
+        };
+
+        self.context
+            .wait(operation, rx, self.context.operation_timeout())
+            .await
+            .unwrap_or(false)
+    }
+
+    /// Makes `devices` the most preferred devices for `role`, first one first.
+    pub async fn reorder_devices_for_role(&mut self, role: &str, devices: &[&str]) -> bool {
+        let (tx, rx) = futures::channel::oneshot::channel::<bool>();
+
+        let operation = {
+            let _lock = self.context.lock();
+            self.ext
+                .reorder_devices_for_role(role, devices, success_callback!(tx))
+        };
+
+        self.context
+            .wait(operation, rx, self.context.operation_timeout())
+            .await
+            .unwrap_or(false)
+    }
+}
+
+/// Yields each time a known device changes, see [`Context::subscribe_device_manager`].
+pub struct DeviceManagerEvents {
+    rx: UnboundedReceiver<()>,
+    ext: ext_device_manager::DeviceManager,
+    context: Context,
+}
+
+impl DeviceManagerEvents {
+    pub(crate) fn new(context: &Context) -> Self {
+        let _lock = context.lock();
+        let mut ext = context.inner.borrow().context.device_manager();
+
+        let (tx, rx) = futures::channel::mpsc::unbounded::<()>();
+        context.close_on_disconnect(tx.clone());
+
+        ext.set_subscribe_cb(move || {
+            tx.unbounded_send(()).ok();
+        });
+        ext.subscribe(true, |_| {});
+
+        Self {
+            rx,
+            ext,
+            context: context.clone(),
+        }
+    }
+}
+
+impl futures::Stream for DeviceManagerEvents {
+    type Item = ();
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.rx.poll_next_unpin(cx)
+    }
+}
+
+impl Drop for DeviceManagerEvents {
+    fn drop(&mut self) {
+        let _lock = self.context.lock();
+        // The callback can only be replaced, not removed
+        self.ext.set_subscribe_cb(|| {});
+
+        // libpulse refuses to unsubscribe once disconnected, there is nothing to stop then
+        if self.context.is_ready() {
+            self.ext.subscribe(false, |_| {});
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_server::{TestServer, SINKS};
+
+    #[test]
+    fn read_and_reorder() {
+        let server = TestServer::spawn();
+
+        futures::executor::block_on(async {
+            let context = server.connect().await;
+            let mut manager = context.device_manager();
+
+            let names: Vec<String> = SINKS.iter().map(|sink| format!("sink:{}", sink)).collect();
+
+            let devices = manager.read().await.unwrap();
+            for name in &names {
+                let device = devices.iter().find(|device| &device.name == name).unwrap();
+                assert!(device.is_sink());
+                assert!(device.index.is_some());
+            }
+
+            let order = [names[1].as_str(), names[0].as_str()];
+            assert!(manager.reorder_devices_for_role("music", &order).await);
+
+            let devices = manager.read().await.unwrap();
+            let priority = |name: &str| {
+                devices
+                    .iter()
+                    .find(|device| device.name == name)
+                    .and_then(|device| device.priority("music"))
+                    .unwrap()
+            };
+            assert!(priority(order[0]) < priority(order[1]));
+
+            assert!(manager.set_device_description(order[0], "Renamed").await);
+            let devices = manager.read().await.unwrap();
+            let renamed = devices.iter().find(|device| device.name == order[0]);
+            assert_eq!(renamed.unwrap().description.as_deref(), Some("Renamed"));
+        });
+    }
+}
@@ -0,0 +1,184 @@
+//! Access to `module-device-restore`, which remembers volume, mute and the formats (e.g. for
+//! passthrough) a device supports.
+
+use futures::{channel::mpsc::UnboundedReceiver, StreamExt};
+use pulse::{
+    context::ext_device_restore::{self, Info},
+    def, format,
+};
+
+use super::{introspector::list_callback, Context};
+
+/// Formats saved for a device.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceFormats {
+    /// Whether `index` is a sink or a source.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::device_type"))]
+    pub device_type: def::Device,
+    pub index: u32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::format_info_list"))]
+    pub formats: Vec<format::Info>,
+}
+
+impl<'a> From<&'a Info> for DeviceFormats {
+    fn from(item: &'a Info) -> Self {
+        Self {
+            device_type: item.dtype,
+            index: item.index,
+            formats: item.formats.clone(),
+        }
+    }
+}
+
+pub struct DeviceRestore<'a> {
+    ext: ext_device_restore::DeviceRestore,
+    context: &'a Context,
+}
+
+impl<'a> DeviceRestore<'a> {
+    pub(crate) fn new(context: &'a Context) -> Self {
+        let _lock = context.lock();
+        let ext = context.inner.borrow().context.device_restore();
+
+        Self { ext, context }
+    }
+
+    /// Gets the saved formats of every device.
+    pub async fn read_formats_all(&mut self) -> Result<Vec<DeviceFormats>, ()> {
+        let (tx, rx) = futures::channel::oneshot::channel();
+
+        let operation = {
+            let _lock = self.context.lock();
+            self.ext
+                .read_formats_all(list_callback!(tx, DeviceFormats::from))
+        };
+
+        self.context
+            .wait(operation, rx, self.context.operation_timeout())
+            .await
+            .unwrap_or(Err(()))
+    }
+
+    /// Gets the saved formats of one sink or source.
+    pub async fn read_formats(
+        &mut self,
+        device_type: def::Device,
+        index: u32,
+    ) -> Result<DeviceFormats, ()> {
+        let (tx, rx) = futures::channel::oneshot::channel();
+
+        let operation = {
+            let _lock = self.context.lock();
+            self.ext
+                .read_formats(device_type, index, list_callback!(tx, DeviceFormats::from))
+        };
+
+        let res: Result<Vec<DeviceFormats>, ()> = self
+            .context
+            .wait(operation, rx, self.context.operation_timeout())
+            .await
+            .unwrap_or(Err(()));
+
+        res.and_then(|mut list| list.pop().ok_or(()))
+    }
+
+    /// Saves the formats a sink or source supports.
+    pub async fn save_formats(
+        &mut self,
+        device_type: def::Device,
+        index: u32,
+        formats: &[format::Info],
+    ) -> bool {
+        let mut formats = formats.to_vec();
+        let mut formats: Vec<&mut format::Info> = formats.iter_mut().collect();
+
+        let (tx, rx) = futures::channel::oneshot::channel::<bool>();
+
+        let mut tx = Some(tx);
+        let operation = {
+            let _lock = self.context.lock();
+            self.ext
+                .save_formats(device_type, index, &mut formats, move |success| {
+                    if let Some(tx) = tx.take() {
+                        tx.send(success).ok();
+                    }
+                })
+        };
+
+        self.context
+            .wait(operation, rx, self.context.operation_timeout())
+            .await
+            .unwrap_or(false)
+    }
+}
+
+/// Yields the device whose saved data changed, see [`Context::subscribe_device_restore`].
+pub struct DeviceRestoreEvents {
+    rx: UnboundedReceiver<(def::Device, u32)>,
+    ext: ext_device_restore::DeviceRestore,
+    context: Context,
+}
+
+impl DeviceRestoreEvents {
+    pub(crate) fn new(context: &Context) -> Self {
+        let _lock = context.lock();
+        let mut ext = context.inner.borrow().context.device_restore();
+
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        context.close_on_disconnect(tx.clone());
+
+        ext.set_subscribe_cb(move |device_type, index| {
+            tx.unbounded_send((device_type, index)).ok();
+        });
+        ext.subscribe(true, |_| {});
+
+        Self {
+            rx,
+            ext,
+            context: context.clone(),
+        }
+    }
+}
+
+impl futures::Stream for DeviceRestoreEvents {
+    type Item = (def::Device, u32);
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.rx.poll_next_unpin(cx)
+    }
+}
+
+impl Drop for DeviceRestoreEvents {
+    fn drop(&mut self) {
+        let _lock = self.context.lock();
+        // The callback can only be replaced, not removed
+        self.ext.set_subscribe_cb(|_, _| {});
+
+        // libpulse refuses to unsubscribe once disconnected, there is nothing to stop then
+        if self.context.is_ready() {
+            self.ext.subscribe(false, |_| {});
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_server::TestServer;
+
+    #[test]
+    fn read_formats() {
+        let server = TestServer::spawn();
+
+        futures::executor::block_on(async {
+            let context = server.connect().await;
+            let mut restore = context.device_restore();
+
+            // Null sinks have nothing saved, but the extension has to answer
+            assert!(restore.read_formats_all().await.is_ok());
+        });
+    }
+}
@@ -0,0 +1,228 @@
+use std::{collections::HashMap, time::Duration};
+
+use pulse::volume::ChannelVolumes;
+
+use crate::{
+    volume::{ChannelVolumesExt, VolumeLevel},
+    Context,
+};
+
+/// Time between two volume changes of a fade.
+const STEP: Duration = Duration::from_millis(50);
+
+/// Where logarithmic fades start or end when fading from or to silence.
+const FLOOR_DB: f64 = -60.0;
+
+/// How the volume moves between the start and the end of a fade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FadeCurve {
+    /// Straight line in amplitude, sounds like most of the change happens at the end.
+    Linear,
+    /// Straight line in dB, sounds even to the ear.
+    Logarithmic,
+}
+
+impl FadeCurve {
+    /// Level at `t` (`0.0..=1.0`) of the way from `from` to `to`.
+    pub fn interpolate(self, from: VolumeLevel, to: VolumeLevel, t: f64) -> VolumeLevel {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            FadeCurve::Linear => {
+                let (from, to) = (from.linear(), to.linear());
+                VolumeLevel::from_linear(from + (to - from) * t)
+            }
+            FadeCurve::Logarithmic => {
+                let (from, to) = (from.db().max(FLOOR_DB), to.db().max(FLOOR_DB));
+                let db = from + (to - from) * t;
+
+                if db <= FLOOR_DB {
+                    VolumeLevel::MUTED
+                } else {
+                    VolumeLevel::from_db(db)
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum FadeTarget {
+    Sink(u32),
+    Source(u32),
+    SinkInput(u32),
+}
+
+/// Fades in progress, so a newer fade of the same object can cancel an older one.
+#[derive(Debug, Default)]
+pub(crate) struct Fades {
+    running: HashMap<FadeTarget, u64>,
+    generation: u64,
+}
+
+impl Fades {
+    fn start(&mut self, target: FadeTarget) -> u64 {
+        self.generation += 1;
+        self.running.insert(target, self.generation);
+        self.generation
+    }
+
+    fn is_current(&self, target: FadeTarget, generation: u64) -> bool {
+        self.running.get(&target) == Some(&generation)
+    }
+
+    fn finish(&mut self, target: FadeTarget, generation: u64) {
+        if self.is_current(target, generation) {
+            self.running.remove(&target);
+        }
+    }
+}
+
+async fn current_volumes(context: &Context, target: FadeTarget) -> Result<ChannelVolumes, ()> {
+    let introspect = context.introspect();
+
+    match target {
+        FadeTarget::Sink(id) => introspect.sink(id).await.map(|info| info.volume),
+        FadeTarget::Source(id) => introspect.source(id).await.map(|info| info.volume),
+        FadeTarget::SinkInput(id) => introspect.sink_input(id).await.map(|info| info.volume),
+    }
+}
+
+async fn apply_volumes(context: &Context, target: FadeTarget, volumes: &ChannelVolumes) -> bool {
+    let mut introspect = context.introspect();
+
+    match target {
+        FadeTarget::Sink(id) => introspect.set_sink_volumes(id, volumes).await,
+        FadeTarget::Source(id) => introspect.set_source_volumes(id, volumes).await,
+        FadeTarget::SinkInput(id) => introspect.set_sink_input_volumes(id, volumes).await,
+    }
+}
+
+/// Moves the volume of `target` to `to` over `duration`, keeping balance and fade.
+///
+/// Resolves with `Ok(true)` once `to` is reached and `Ok(false)` if a newer fade of the same object
+/// took over. Fails if the object went away.
+pub(crate) async fn fade(
+    context: &Context,
+    target: FadeTarget,
+    to: VolumeLevel,
+    duration: Duration,
+    curve: FadeCurve,
+) -> Result<bool, ()> {
+    let generation = context.inner.borrow_mut().fades.start(target);
+    let is_current = || context.inner.borrow().fades.is_current(target, generation);
+
+    let mut volumes = current_volumes(context, target).await?;
+    let from = volumes.level();
+
+    let steps = (duration.as_secs_f64() / STEP.as_secs_f64())
+        .ceil()
+        .max(1.0) as u32;
+    let interval = duration / steps;
+
+    for step in 1..=steps {
+        context.sleep(interval).await;
+
+        if !is_current() {
+            return Ok(false);
+        }
+
+        let level = if step == steps {
+            to
+        } else {
+            curve.interpolate(from, to, step as f64 / steps as f64)
+        };
+        volumes.set_level(level);
+
+        if !apply_volumes(context, target, &volumes).await {
+            context.inner.borrow_mut().fades.finish(target, generation);
+            return Err(());
+        }
+    }
+
+    let finished = is_current();
+    context.inner.borrow_mut().fades.finish(target, generation);
+
+    Ok(finished)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future;
+    use pulse::volume::Volume;
+
+    use super::*;
+    use crate::test_server::TestServer;
+
+    #[test]
+    fn curves() {
+        let half = VolumeLevel::from_percent(50.0);
+
+        for curve in [FadeCurve::Linear, FadeCurve::Logarithmic] {
+            assert_eq!(
+                curve.interpolate(VolumeLevel::NORMAL, half, 0.0),
+                VolumeLevel::NORMAL
+            );
+            assert_eq!(curve.interpolate(VolumeLevel::NORMAL, half, 1.0), half);
+            assert_eq!(
+                curve.interpolate(VolumeLevel::NORMAL, VolumeLevel::MUTED, 1.0),
+                VolumeLevel::MUTED
+            );
+        }
+
+        let linear = FadeCurve::Linear.interpolate(VolumeLevel::NORMAL, VolumeLevel::MUTED, 0.5);
+        assert!((linear.linear() - 0.5).abs() < 1e-3);
+
+        let log = FadeCurve::Logarithmic.interpolate(VolumeLevel::NORMAL, VolumeLevel::MUTED, 0.5);
+        assert!((log.db() - FLOOR_DB / 2.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn fade_and_cancel() {
+        let server = TestServer::spawn();
+
+        futures::executor::block_on(async {
+            let context = server.connect().await;
+            let sink = context.introspect().sink_list().await.unwrap().remove(0);
+            let half = VolumeLevel::from_percent(50.0);
+
+            let done = context
+                .fade_sink_volume(
+                    sink.index,
+                    half,
+                    Duration::from_millis(200),
+                    FadeCurve::Linear,
+                )
+                .await;
+            assert_eq!(done, Ok(true));
+
+            let info = context.introspect().sink(sink.index).await.unwrap();
+            assert_eq!(info.volume.max(), half.volume());
+
+            let slow = context.fade_sink_volume(
+                sink.index,
+                VolumeLevel::MUTED,
+                Duration::from_secs(5),
+                FadeCurve::Logarithmic,
+            );
+            let fast = async {
+                context.sleep(Duration::from_millis(100)).await;
+                context
+                    .fade_sink_volume(
+                        sink.index,
+                        VolumeLevel::NORMAL,
+                        Duration::ZERO,
+                        FadeCurve::Linear,
+                    )
+                    .await
+            };
+
+            let (slow, fast) = future::join(slow, fast).await;
+            assert_eq!(slow, Ok(false));
+            assert_eq!(fast, Ok(true));
+
+            let info = context.introspect().sink(sink.index).await.unwrap();
+            assert_eq!(info.volume.max(), Volume::NORMAL);
+        });
+    }
+}
@@ -1,24 +1,37 @@
-use std::marker::PhantomData;
+use std::time::Duration;
 
 use pulse::{
     context::introspect::{self, CardInfo, ClientInfo},
-    volume::{ChannelVolumes, Volume},
+    volume::ChannelVolumes,
 };
 
+use super::Context;
+use crate::volume::VolumeLevel;
+
 pub use data::*;
 
 pub struct Introspector<'a> {
     introspector: introspect::Introspector,
-    pd: PhantomData<&'a ()>,
+    context: &'a Context,
+    timeout: Option<Duration>,
 }
 
-impl<'a> From<introspect::Introspector> for Introspector<'a> {
-    fn from(introspector: introspect::Introspector) -> Self {
+impl<'a> Introspector<'a> {
+    pub(crate) fn new(context: &'a Context, introspector: introspect::Introspector) -> Self {
         Self {
             introspector,
-            pd: PhantomData,
+            context,
+            timeout: context.operation_timeout(),
         }
     }
+
+    /// Overrides [`Context::operation_timeout`] for the operations of this introspector.
+    ///
+    /// Operations that time out fail and are cancelled.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
 }
 
 macro_rules! list_callback {
@@ -36,63 +49,130 @@ macro_rules! list_callback {
             }
             pulse::callbacks::ListResult::End => {
                 if let (Some(tx), Some(list)) = (tx.take(), list.take()) {
-                    tx.send(Ok(list)).unwrap();
+                    tx.send(Ok(list)).ok();
                 }
             }
             pulse::callbacks::ListResult::Error => {
                 if let Some(tx) = tx.take() {
-                    tx.send(Err(())).unwrap();
+                    tx.send(Err(())).ok();
                 }
             }
         }
     }};
 }
 
+pub(crate) use list_callback;
+
 impl<'a> Introspector<'a> {
     /// Gets the card list.
     pub async fn card_list(&self) -> Result<Vec<String>, ()> {
         let (tx, rx) = futures::channel::oneshot::channel();
 
-        self.introspector
-            .get_card_info_list(list_callback!(tx, |item: &CardInfo| {
-                item.name.as_ref().unwrap().to_string()
-            }));
+        let operation = {
+            let _lock = self.context.lock();
+            self.introspector
+                .get_card_info_list(list_callback!(tx, |item: &CardInfo| {
+                    item.name.as_ref().unwrap().to_string()
+                }))
+        };
 
-        rx.await.unwrap()
+        self.context
+            .wait(operation, rx, self.timeout)
+            .await
+            .unwrap_or(Err(()))
     }
 
     /// Gets the client list.
     pub async fn client_list(&self) -> Result<Vec<String>, ()> {
         let (tx, rx) = futures::channel::oneshot::channel();
 
-        self.introspector
-            .get_client_info_list(list_callback!(tx, |item: &ClientInfo| {
-                item.name.as_ref().unwrap().to_string()
-            }));
+        let operation = {
+            let _lock = self.context.lock();
+            self.introspector
+                .get_client_info_list(list_callback!(tx, |item: &ClientInfo| {
+                    item.name.as_ref().unwrap().to_string()
+                }))
+        };
+
+        self.context
+            .wait(operation, rx, self.timeout)
+            .await
+            .unwrap_or(Err(()))
+    }
+
+    /// Gets some information about the server.
+    pub async fn server_info(&self) -> Result<data::ServerInfo, ()> {
+        let (tx, rx) = futures::channel::oneshot::channel();
+
+        let mut tx = Some(tx);
+        let operation = {
+            let _lock = self.context.lock();
+            self.introspector.get_server_info(move |info| {
+                if let Some(tx) = tx.take() {
+                    tx.send(data::ServerInfo::from(info)).ok();
+                }
+            })
+        };
+
+        self.context
+            .wait(operation, rx, self.timeout)
+            .await
+            .ok_or(())
+    }
+
+    /// Gets memory block and sample cache statistics of the server.
+    pub async fn stat(&self) -> Result<data::StatInfo, ()> {
+        let (tx, rx) = futures::channel::oneshot::channel();
+
+        let mut tx = Some(tx);
+        let operation = {
+            let _lock = self.context.lock();
+            self.introspector.stat(move |info| {
+                if let Some(tx) = tx.take() {
+                    tx.send(data::StatInfo::from(info)).ok();
+                }
+            })
+        };
 
-        rx.await.unwrap()
+        self.context
+            .wait(operation, rx, self.timeout)
+            .await
+            .ok_or(())
     }
 
     /// Gets the sink list.
     pub async fn sink_list(&self) -> Result<Vec<data::SinkInfo>, ()> {
         let (tx, rx) = futures::channel::oneshot::channel();
 
-        self.introspector
-            .get_sink_info_list(list_callback!(tx, data::SinkInfo::from));
+        let operation = {
+            let _lock = self.context.lock();
+            self.introspector
+                .get_sink_info_list(list_callback!(tx, data::SinkInfo::from))
+        };
 
-        rx.await.unwrap()
+        self.context
+            .wait(operation, rx, self.timeout)
+            .await
+            .unwrap_or(Err(()))
     }
 
     /// Gets information about a sink by its index.
     pub async fn sink(&self, id: u32) -> Result<data::SinkInfo, ()> {
         let (tx, rx) = futures::channel::oneshot::channel();
 
-        self.introspector
-            .get_sink_info_by_index(id, list_callback!(tx, data::SinkInfo::from));
+        let operation = {
+            let _lock = self.context.lock();
+            self.introspector
+                .get_sink_info_by_index(id, list_callback!(tx, data::SinkInfo::from))
+        };
 
-        let res = rx.await.unwrap();
+        let res = self
+            .context
+            .wait(operation, rx, self.timeout)
+            .await
+            .unwrap_or(Err(()));
 
-        res.map(|mut list| list.remove(0))
+        res.and_then(|list| list.into_iter().next().ok_or(()))
     }
 
     /// Sets the volume of a sink input stream.
@@ -101,45 +181,184 @@ impl<'a> Introspector<'a> {
     pub async fn set_sink_volume(&mut self, id: u32, v: f64) -> bool {
         let mut vol = ChannelVolumes::default();
 
-        let v = (Volume::NORMAL.0 as f64 * v / 100.0).round() as u32;
-        vol.set(2, Volume(v));
+        vol.set(2, VolumeLevel::from_percent(v).volume());
+
+        self.set_sink_volumes(id, &vol).await
+    }
 
+    /// Sets the volume of every channel of a sink.
+    pub async fn set_sink_volumes(&mut self, id: u32, volume: &ChannelVolumes) -> bool {
         let (tx, rx) = futures::channel::oneshot::channel::<bool>();
 
         let mut tx = Some(tx);
-        self.introspector.set_sink_volume_by_index(
-            id,
-            &vol,
-            Some(Box::new(move |success| {
-                if let Some(tx) = tx.take() {
-                    tx.send(success).unwrap();
-                }
-            })),
-        );
+        let operation = {
+            let _lock = self.context.lock();
+            self.introspector.set_sink_volume_by_index(
+                id,
+                volume,
+                Some(Box::new(move |success| {
+                    if let Some(tx) = tx.take() {
+                        tx.send(success).ok();
+                    }
+                })),
+            )
+        };
+
+        self.context
+            .wait(operation, rx, self.timeout)
+            .await
+            .unwrap_or(false)
+    }
+
+    /// Gets the source list.
+    pub async fn source_list(&self) -> Result<Vec<data::SourceInfo>, ()> {
+        let (tx, rx) = futures::channel::oneshot::channel();
+
+        let operation = {
+            let _lock = self.context.lock();
+            self.introspector
+                .get_source_info_list(list_callback!(tx, data::SourceInfo::from))
+        };
+
+        self.context
+            .wait(operation, rx, self.timeout)
+            .await
+            .unwrap_or(Err(()))
+    }
+
+    /// Gets information about a source by its index.
+    pub async fn source(&self, id: u32) -> Result<data::SourceInfo, ()> {
+        let (tx, rx) = futures::channel::oneshot::channel();
+
+        let operation = {
+            let _lock = self.context.lock();
+            self.introspector
+                .get_source_info_by_index(id, list_callback!(tx, data::SourceInfo::from))
+        };
+
+        let res = self
+            .context
+            .wait(operation, rx, self.timeout)
+            .await
+            .unwrap_or(Err(()));
+
+        res.and_then(|list| list.into_iter().next().ok_or(()))
+    }
+
+    /// Sets the volume of a source in percent, see [`Introspector::set_sink_volume`].
+    pub async fn set_source_volume(&mut self, id: u32, v: f64) -> bool {
+        let mut vol = ChannelVolumes::default();
+
+        vol.set(2, VolumeLevel::from_percent(v).volume());
+
+        self.set_source_volumes(id, &vol).await
+    }
+
+    /// Sets the volume of every channel of a source.
+    pub async fn set_source_volumes(&mut self, id: u32, volume: &ChannelVolumes) -> bool {
+        let (tx, rx) = futures::channel::oneshot::channel::<bool>();
+
+        let mut tx = Some(tx);
+        let operation = {
+            let _lock = self.context.lock();
+            self.introspector.set_source_volume_by_index(
+                id,
+                volume,
+                Some(Box::new(move |success| {
+                    if let Some(tx) = tx.take() {
+                        tx.send(success).ok();
+                    }
+                })),
+            )
+        };
+
+        self.context
+            .wait(operation, rx, self.timeout)
+            .await
+            .unwrap_or(false)
+    }
+
+    /// Mutes or unmutes a source.
+    pub async fn set_source_mute(&mut self, id: u32, mute: bool) -> bool {
+        let (tx, rx) = futures::channel::oneshot::channel::<bool>();
+
+        let mut tx = Some(tx);
+        let operation = {
+            let _lock = self.context.lock();
+            self.introspector.set_source_mute_by_index(
+                id,
+                mute,
+                Some(Box::new(move |success| {
+                    if let Some(tx) = tx.take() {
+                        tx.send(success).ok();
+                    }
+                })),
+            )
+        };
+
+        self.context
+            .wait(operation, rx, self.timeout)
+            .await
+            .unwrap_or(false)
+    }
+
+    /// Switches a source to the port named `port`.
+    pub async fn set_source_port(&mut self, id: u32, port: &str) -> bool {
+        let (tx, rx) = futures::channel::oneshot::channel::<bool>();
+
+        let mut tx = Some(tx);
+        let operation = {
+            let _lock = self.context.lock();
+            self.introspector.set_source_port_by_index(
+                id,
+                port,
+                Some(Box::new(move |success| {
+                    if let Some(tx) = tx.take() {
+                        tx.send(success).ok();
+                    }
+                })),
+            )
+        };
 
-        rx.await.unwrap()
+        self.context
+            .wait(operation, rx, self.timeout)
+            .await
+            .unwrap_or(false)
     }
 
     /// Gets the sink input list.
     pub async fn sink_input_list(&self) -> Result<Vec<data::SinkInputInfo>, ()> {
         let (tx, rx) = futures::channel::oneshot::channel();
 
-        self.introspector
-            .get_sink_input_info_list(list_callback!(tx, data::SinkInputInfo::from));
+        let operation = {
+            let _lock = self.context.lock();
+            self.introspector
+                .get_sink_input_info_list(list_callback!(tx, data::SinkInputInfo::from))
+        };
 
-        rx.await.unwrap()
+        self.context
+            .wait(operation, rx, self.timeout)
+            .await
+            .unwrap_or(Err(()))
     }
 
     /// Gets some information about a sink input by its index.
     pub async fn sink_input(&self, id: u32) -> Result<data::SinkInputInfo, ()> {
         let (tx, rx) = futures::channel::oneshot::channel();
 
-        self.introspector
-            .get_sink_input_info(id, list_callback!(tx, data::SinkInputInfo::from));
+        let operation = {
+            let _lock = self.context.lock();
+            self.introspector
+                .get_sink_input_info(id, list_callback!(tx, data::SinkInputInfo::from))
+        };
 
-        let res = rx.await.unwrap();
+        let res = self
+            .context
+            .wait(operation, rx, self.timeout)
+            .await
+            .unwrap_or(Err(()));
 
-        res.map(|mut list| list.remove(0))
+        res.and_then(|list| list.into_iter().next().ok_or(()))
     }
 
     /// Sets the volume of a sink input stream.
@@ -148,23 +367,127 @@ impl<'a> Introspector<'a> {
     pub async fn set_sink_input_volume(&mut self, id: u32, v: f64) -> bool {
         let mut vol = ChannelVolumes::default();
 
-        let v = (Volume::NORMAL.0 as f64 * v / 100.0).round() as u32;
-        vol.set(2, Volume(v));
+        vol.set(2, VolumeLevel::from_percent(v).volume());
+
+        self.set_sink_input_volumes(id, &vol).await
+    }
 
+    /// Sets the volume of every channel of a sink input stream.
+    pub async fn set_sink_input_volumes(&mut self, id: u32, volume: &ChannelVolumes) -> bool {
         let (tx, rx) = futures::channel::oneshot::channel::<bool>();
 
         let mut tx = Some(tx);
-        self.introspector.set_sink_input_volume(
-            id,
-            &vol,
-            Some(Box::new(move |success| {
-                if let Some(tx) = tx.take() {
-                    tx.send(success).unwrap();
-                }
-            })),
-        );
+        let operation = {
+            let _lock = self.context.lock();
+            self.introspector.set_sink_input_volume(
+                id,
+                volume,
+                Some(Box::new(move |success| {
+                    if let Some(tx) = tx.take() {
+                        tx.send(success).ok();
+                    }
+                })),
+            )
+        };
+
+        self.context
+            .wait(operation, rx, self.timeout)
+            .await
+            .unwrap_or(false)
+    }
+
+    /// Gets the source output list.
+    pub async fn source_output_list(&self) -> Result<Vec<data::SourceOutputInfo>, ()> {
+        let (tx, rx) = futures::channel::oneshot::channel();
+
+        let operation = {
+            let _lock = self.context.lock();
+            self.introspector
+                .get_source_output_info_list(list_callback!(tx, data::SourceOutputInfo::from))
+        };
+
+        self.context
+            .wait(operation, rx, self.timeout)
+            .await
+            .unwrap_or(Err(()))
+    }
+
+    /// Gets some information about a source output by its index.
+    pub async fn source_output(&self, id: u32) -> Result<data::SourceOutputInfo, ()> {
+        let (tx, rx) = futures::channel::oneshot::channel();
+
+        let operation = {
+            let _lock = self.context.lock();
+            self.introspector
+                .get_source_output_info(id, list_callback!(tx, data::SourceOutputInfo::from))
+        };
+
+        let res = self
+            .context
+            .wait(operation, rx, self.timeout)
+            .await
+            .unwrap_or(Err(()));
+
+        res.and_then(|list| list.into_iter().next().ok_or(()))
+    }
+
+    /// Sets the volume of a source output stream.
+    ///
+    /// The callback accepts a `bool`, which indicates success.
+    pub async fn set_source_output_volume(&mut self, id: u32, v: f64) -> bool {
+        let mut vol = ChannelVolumes::default();
 
-        rx.await.unwrap()
+        vol.set(2, VolumeLevel::from_percent(v).volume());
+
+        self.set_source_output_volumes(id, &vol).await
+    }
+
+    /// Sets the volume of every channel of a source output stream.
+    pub async fn set_source_output_volumes(&mut self, id: u32, volume: &ChannelVolumes) -> bool {
+        let (tx, rx) = futures::channel::oneshot::channel::<bool>();
+
+        let mut tx = Some(tx);
+        let operation = {
+            let _lock = self.context.lock();
+            self.introspector.set_source_output_volume(
+                id,
+                volume,
+                Some(Box::new(move |success| {
+                    if let Some(tx) = tx.take() {
+                        tx.send(success).ok();
+                    }
+                })),
+            )
+        };
+
+        self.context
+            .wait(operation, rx, self.timeout)
+            .await
+            .unwrap_or(false)
+    }
+
+    /// Mutes or unmutes a source output stream.
+    pub async fn set_source_output_mute(&mut self, id: u32, mute: bool) -> bool {
+        let (tx, rx) = futures::channel::oneshot::channel::<bool>();
+
+        let mut tx = Some(tx);
+        let operation = {
+            let _lock = self.context.lock();
+            self.introspector.set_source_output_mute(
+                id,
+                mute,
+                Some(Box::new(move |success| {
+                    if let Some(tx) = tx.take() {
+                        tx.send(success).ok();
+                    }
+                })),
+            )
+        };
+
+        self.context
+            .wait(operation, rx, self.timeout)
+            .await
+            .unwrap_or(false)
     }
 }
 
@@ -180,6 +503,90 @@ mod data {
         volume::{ChannelVolumes, Volume},
     };
 
+    /*
+     * Server info
+     */
+
+    /// Server information.
+    ///
+    /// Please note that this structure can be extended as part of evolutionary API updates at any time
+    /// in any new release.
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct ServerInfo {
+        /// User name of the daemon process.
+        pub user_name: Option<String>,
+        /// Host name the daemon is running on.
+        pub host_name: Option<String>,
+        /// Version string of the daemon.
+        pub server_version: Option<String>,
+        /// Server package name (usually “pulseaudio”).
+        pub server_name: Option<String>,
+        /// Default sample specification.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::sample_spec"))]
+        pub sample_spec: sample::Spec,
+        /// Name of default sink.
+        pub default_sink_name: Option<String>,
+        /// Name of default source.
+        pub default_source_name: Option<String>,
+        /// A random cookie for identifying this instance of PulseAudio.
+        pub cookie: u32,
+        /// Default channel map.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::channel_map"))]
+        pub channel_map: channelmap::Map,
+    }
+
+    impl<'a> From<&'a introspect::ServerInfo<'a>> for ServerInfo {
+        fn from(item: &'a introspect::ServerInfo<'a>) -> Self {
+            ServerInfo {
+                user_name: item.user_name.as_ref().map(|cow| cow.to_string()),
+                host_name: item.host_name.as_ref().map(|cow| cow.to_string()),
+                server_version: item.server_version.as_ref().map(|cow| cow.to_string()),
+                server_name: item.server_name.as_ref().map(|cow| cow.to_string()),
+                sample_spec: item.sample_spec,
+                default_sink_name: item.default_sink_name.as_ref().map(|cow| cow.to_string()),
+                default_source_name: item.default_source_name.as_ref().map(|cow| cow.to_string()),
+                cookie: item.cookie,
+                channel_map: item.channel_map,
+            }
+        }
+    }
+
+    /*
+     * Statistics
+     */
+
+    /// Memory block statistics.
+    ///
+    /// Please note that this structure can be extended as part of evolutionary API updates at any time
+    /// in any new release.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct StatInfo {
+        /// Currently allocated memory blocks.
+        pub memblock_total: u32,
+        /// Current total size of allocated memory blocks.
+        pub memblock_total_size: u32,
+        /// Allocated memory blocks during the whole lifetime of the daemon.
+        pub memblock_allocated: u32,
+        /// Total size of all memory blocks allocated during the whole lifetime of the daemon.
+        pub memblock_allocated_size: u32,
+        /// Total size of all sample cache entries.
+        pub scache_size: u32,
+    }
+
+    impl<'a> From<&'a introspect::StatInfo> for StatInfo {
+        fn from(item: &'a introspect::StatInfo) -> Self {
+            StatInfo {
+                memblock_total: item.memblock_total,
+                memblock_total_size: item.memblock_total_size,
+                memblock_allocated: item.memblock_allocated,
+                memblock_allocated_size: item.memblock_allocated_size,
+                scache_size: item.scache_size,
+            }
+        }
+    }
+
     /*
      * Sink info
      */
@@ -188,7 +595,8 @@ mod data {
     ///
     /// Please note that this structure can be extended as part of evolutionary API updates at any time
     /// in any new release.
-    #[derive(Debug)]
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct SinkPortInfo {
         /// Name of the sink.
         pub name: Option<String>,
@@ -197,6 +605,7 @@ mod data {
         /// The higher this value is, the more useful this port is as a default.
         pub priority: u32,
         /// A flag indicating availability status of this port.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::port_available"))]
         pub available: PortAvailable,
     }
 
@@ -221,7 +630,8 @@ mod data {
     ///
     /// Please note that this structure can be extended as part of evolutionary API updates at any time
     /// in any new release.
-    #[derive(Debug)]
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct SinkInfo {
         /// Name of the sink.
         pub name: Option<String>,
@@ -230,12 +640,15 @@ mod data {
         /// Description of this sink.
         pub description: Option<String>,
         /// Sample spec of this sink.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::sample_spec"))]
         pub sample_spec: sample::Spec,
         /// Channel map.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::channel_map"))]
         pub channel_map: channelmap::Map,
         /// Index of the owning module of this sink, or `None` if is invalid.
         pub owner_module: Option<u32>,
         /// Volume of the sink.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::channel_volumes"))]
         pub volume: ChannelVolumes,
         /// Mute switch of the sink.
         pub mute: bool,
@@ -244,19 +657,25 @@ mod data {
         /// The name of the monitor source.
         pub monitor_source_name: Option<String>,
         /// Length of queued audio in the output buffer.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::micro_seconds"))]
         pub latency: MicroSeconds,
         /// Driver name.
         pub driver: Option<String>,
         /// Flags.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::sink_flags"))]
         pub flags: def::SinkFlagSet,
         /// Property list.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::proplist"))]
         pub proplist: Proplist,
         /// The latency this device has been configured to.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::micro_seconds"))]
         pub configured_latency: MicroSeconds,
         /// Some kind of “base” volume that refers to unamplified/unattenuated volume in the context of
         /// the output device.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::volume"))]
         pub base_volume: Volume,
         /// State.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::sink_state"))]
         pub state: def::SinkState,
         /// Number of volume steps for sinks which do not support arbitrary volumes.
         pub n_volume_steps: u32,
@@ -267,6 +686,7 @@ mod data {
         // Pointer to active port in the set, or None.
         pub active_port: Option<SinkPortInfo>,
         /// Set of formats supported by the sink.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::format_info_list"))]
         pub formats: Vec<format::Info>,
     }
 
@@ -299,6 +719,141 @@ mod data {
         }
     }
 
+    /*
+     * Source info
+     */
+
+    /// Stores information about a specific port of a source.
+    ///
+    /// Please note that this structure can be extended as part of evolutionary API updates at any time
+    /// in any new release.
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct SourcePortInfo {
+        /// Name of the source.
+        pub name: Option<String>,
+        /// Description of this source.
+        pub description: Option<String>,
+        /// The higher this value is, the more useful this port is as a default.
+        pub priority: u32,
+        /// A flag indicating availability status of this port.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::port_available"))]
+        pub available: PortAvailable,
+    }
+
+    impl<'a> From<&'a introspect::SourcePortInfo<'a>> for SourcePortInfo {
+        fn from(item: &'a introspect::SourcePortInfo<'a>) -> Self {
+            SourcePortInfo {
+                name: item.name.as_ref().map(|cow| cow.to_string()),
+                description: item.description.as_ref().map(|cow| cow.to_string()),
+                priority: item.priority,
+                available: item.available,
+            }
+        }
+    }
+
+    impl<'a> From<&'a Box<introspect::SourcePortInfo<'a>>> for SourcePortInfo {
+        fn from(item: &'a Box<introspect::SourcePortInfo<'a>>) -> Self {
+            item.as_ref().into()
+        }
+    }
+
+    /// Stores information about sources.
+    ///
+    /// Please note that this structure can be extended as part of evolutionary API updates at any time
+    /// in any new release.
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct SourceInfo {
+        /// Name of the source.
+        pub name: Option<String>,
+        /// Index of the source.
+        pub index: u32,
+        /// Description of this source.
+        pub description: Option<String>,
+        /// Sample spec of this source.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::sample_spec"))]
+        pub sample_spec: sample::Spec,
+        /// Channel map.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::channel_map"))]
+        pub channel_map: channelmap::Map,
+        /// Owning module index, or `None`.
+        pub owner_module: Option<u32>,
+        /// Volume of the source.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::channel_volumes"))]
+        pub volume: ChannelVolumes,
+        /// Mute switch of the sink.
+        pub mute: bool,
+        /// If this is a monitor source, the index of the owning sink, otherwise `None`.
+        pub monitor_of_sink: Option<u32>,
+        /// Name of the owning sink, or `None`.
+        pub monitor_of_sink_name: Option<String>,
+        /// Length of filled record buffer of this source.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::micro_seconds"))]
+        pub latency: MicroSeconds,
+        /// Driver name.
+        pub driver: Option<String>,
+        /// Flags.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::source_flags"))]
+        pub flags: def::SourceFlagSet,
+        /// Property list.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::proplist"))]
+        pub proplist: Proplist,
+        /// The latency this device has been configured to.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::micro_seconds"))]
+        pub configured_latency: MicroSeconds,
+        /// Some kind of “base” volume that refers to unamplified/unattenuated volume in the context of
+        /// the input device.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::volume"))]
+        pub base_volume: Volume,
+        /// State.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::source_state"))]
+        pub state: def::SourceState,
+        /// Number of volume steps for sources which do not support arbitrary volumes.
+        pub n_volume_steps: u32,
+        /// Card index, or `None`.
+        pub card: Option<u32>,
+        /// Set of available ports.
+        pub ports: Vec<SourcePortInfo>,
+        /// Pointer to active port in the set, or `None`.
+        pub active_port: Option<SourcePortInfo>,
+        /// Set of formats supported by the source.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::format_info_list"))]
+        pub formats: Vec<format::Info>,
+    }
+
+    impl<'a> From<&'a introspect::SourceInfo<'a>> for SourceInfo {
+        fn from(item: &'a introspect::SourceInfo<'a>) -> Self {
+            SourceInfo {
+                name: item.name.as_ref().map(|cow| cow.to_string()),
+                index: item.index,
+                description: item.description.as_ref().map(|cow| cow.to_string()),
+                sample_spec: item.sample_spec,
+                channel_map: item.channel_map,
+                owner_module: item.owner_module,
+                volume: item.volume,
+                mute: item.mute,
+                monitor_of_sink: item.monitor_of_sink,
+                monitor_of_sink_name: item
+                    .monitor_of_sink_name
+                    .as_ref()
+                    .map(|cow| cow.to_string()),
+                latency: item.latency,
+                driver: item.driver.as_ref().map(|cow| cow.to_string()),
+                flags: item.flags,
+                proplist: item.proplist.clone(),
+                configured_latency: item.configured_latency,
+                base_volume: item.base_volume,
+                state: item.state,
+                n_volume_steps: item.n_volume_steps,
+                card: item.card,
+                ports: item.ports.iter().map(From::from).collect(),
+                active_port: item.active_port.as_ref().map(From::from),
+                formats: item.formats.clone(),
+            }
+        }
+    }
+
     /*
      * Sink input info
      */
@@ -307,7 +862,8 @@ mod data {
     ///
     /// Please note that this structure can be extended as part of evolutionary API updates at any time
     /// in any new release.
-    #[derive(Debug)]
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct SinkInputInfo {
         /// Index of the sink input.
         pub index: u32,
@@ -322,15 +878,20 @@ mod data {
         /// Index of the connected sink.
         pub sink: u32,
         /// The sample specification of the sink input.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::sample_spec"))]
         pub sample_spec: sample::Spec,
         /// Channel map.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::channel_map"))]
         pub channel_map: channelmap::Map,
         /// The volume of this sink input.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::channel_volumes"))]
         pub volume: ChannelVolumes,
         /// Latency due to buffering in sink input, see [`TimingInfo`](crate::def::TimingInfo) for
         /// details.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::micro_seconds"))]
         pub buffer_usec: MicroSeconds,
         /// Latency of the sink device, see [`TimingInfo`](crate::def::TimingInfo) for details.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::micro_seconds"))]
         pub sink_usec: MicroSeconds,
         /// The resampling method used by this sink input.
         pub resample_method: Option<String>,
@@ -339,6 +900,7 @@ mod data {
         /// Stream muted.
         pub mute: bool,
         /// Property list.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::proplist"))]
         pub proplist: Proplist,
         /// Stream corked.
         pub corked: bool,
@@ -349,6 +911,7 @@ mod data {
         /// control the volume.
         pub volume_writable: bool,
         /// Stream format information.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::format_info"))]
         pub format: format::Info,
     }
 
@@ -377,50 +940,296 @@ mod data {
             }
         }
     }
+
+    /*
+     * Source output info
+     */
+
+    /// Stores information about source outputs.
+    ///
+    /// Please note that this structure can be extended as part of evolutionary API updates at any time
+    /// in any new release.
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct SourceOutputInfo {
+        /// Index of the source output.
+        pub index: u32,
+        /// Name of the source output.
+        pub name: Option<String>,
+        /// Index of the module this source output belongs to, or `None` when it does not belong to
+        /// any module.
+        pub owner_module: Option<u32>,
+        /// Index of the client this source output belongs to, or `None` when it does not belong to
+        /// any client.
+        pub client: Option<u32>,
+        /// Index of the connected source.
+        pub source: u32,
+        /// The sample specification of the source output.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::sample_spec"))]
+        pub sample_spec: sample::Spec,
+        /// Channel map.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::channel_map"))]
+        pub channel_map: channelmap::Map,
+        /// Latency due to buffering in the source output, see
+        /// [`TimingInfo`](crate::def::TimingInfo) for details.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::micro_seconds"))]
+        pub buffer_usec: MicroSeconds,
+        /// Latency of the source device, see [`TimingInfo`](crate::def::TimingInfo) for details.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::micro_seconds"))]
+        pub source_usec: MicroSeconds,
+        /// The resampling method used by this source output.
+        pub resample_method: Option<String>,
+        /// Driver name.
+        pub driver: Option<String>,
+        /// Property list.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::proplist"))]
+        pub proplist: Proplist,
+        /// Stream corked.
+        pub corked: bool,
+        /// The volume of this source output.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::channel_volumes"))]
+        pub volume: ChannelVolumes,
+        /// Stream muted.
+        pub mute: bool,
+        /// Stream has volume. If not set, then the meaning of this struct’s volume member is
+        /// unspecified.
+        pub has_volume: bool,
+        /// The volume can be set. If not set, the volume can still change even though clients can’t
+        /// control the volume.
+        pub volume_writable: bool,
+        /// Stream format information.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::format_info"))]
+        pub format: format::Info,
+    }
+
+    impl<'a> From<&'a introspect::SourceOutputInfo<'a>> for SourceOutputInfo {
+        fn from(item: &'a introspect::SourceOutputInfo<'a>) -> Self {
+            Self {
+                index: item.index,
+                name: item.name.as_ref().map(|cow| cow.to_string()),
+                owner_module: item.owner_module,
+                client: item.client,
+                source: item.source,
+                sample_spec: item.sample_spec,
+                channel_map: item.channel_map,
+                buffer_usec: item.buffer_usec,
+                source_usec: item.source_usec,
+                resample_method: item.resample_method.as_ref().map(|cow| cow.to_string()),
+                driver: item.driver.as_ref().map(|cow| cow.to_string()),
+                proplist: item.proplist.clone(),
+                corked: item.corked,
+                volume: item.volume,
+                mute: item.mute,
+                has_volume: item.has_volume,
+                volume_writable: item.volume_writable,
+                format: item.format.clone(),
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::super::Context;
-    use pulse::{context::FlagSet, proplist::Proplist};
+    use std::time::Duration;
+
+    use futures::StreamExt;
+    use pulse::volume::{ChannelVolumes, Volume};
+
+    use crate::test_server::{TestServer, SINKS};
 
     #[test]
     fn lists() {
-        let props = Proplist::new().unwrap();
-        let context = Context::new_with_proplist("Test", &props);
-
-        glib::MainContext::default().block_on(async move {
-            context.connect(None, FlagSet::NOFLAGS).await.unwrap();
+        let server = TestServer::spawn();
 
+        futures::executor::block_on(async {
+            let context = server.connect().await;
             let introspect = context.introspect();
-            let res = introspect.card_list().await.unwrap();
-            dbg!(res.len());
-            let res = introspect.client_list().await.unwrap();
-            dbg!(res.len());
-            let res = introspect.sink_list().await.unwrap();
-            dbg!(res.len());
-            let res = introspect.sink_input_list().await.unwrap();
-            dbg!(res.len());
+
+            let sinks = introspect.sink_list().await.unwrap();
+            let mut names: Vec<String> =
+                sinks.iter().filter_map(|sink| sink.name.clone()).collect();
+            names.sort();
+            assert_eq!(names, SINKS);
+
+            let sources = introspect.source_list().await.unwrap();
+            for sink in &sinks {
+                let monitor = sources
+                    .iter()
+                    .find(|source| source.index == sink.monitor_source)
+                    .unwrap();
+                assert_eq!(monitor.monitor_of_sink, Some(sink.index));
+            }
+
+            assert!(introspect.card_list().await.unwrap().is_empty());
+            assert!(introspect
+                .client_list()
+                .await
+                .unwrap()
+                .iter()
+                .any(|name| name == "Test"));
+            assert!(introspect.sink_input_list().await.unwrap().is_empty());
+            assert!(introspect.source_output_list().await.unwrap().is_empty());
+
+            let info = introspect.server_info().await.unwrap();
+            let default_sink = info.default_sink_name.unwrap();
+            assert!(SINKS.contains(&default_sink.as_str()));
+
+            let stat = introspect.stat().await.unwrap();
+            assert!(stat.memblock_allocated >= stat.memblock_total);
+            assert!(stat.memblock_allocated_size >= stat.memblock_total_size);
+            assert_eq!(stat.scache_size, 0);
         });
     }
 
     #[test]
     fn item() {
-        let props = Proplist::new().unwrap();
-        let context = Context::new_with_proplist("Test", &props);
+        let server = TestServer::spawn();
 
-        glib::MainContext::default().block_on(async move {
-            context.connect(None, FlagSet::NOFLAGS).await.unwrap();
+        futures::executor::block_on(async {
+            let context = server.connect().await;
 
-            let introspect = context.introspect();
-            // let res = introspect.card_list().await.unwrap();
-            // dbg!(res);
-            // let res = introspect.client_list().await.unwrap();
-            // dbg!(res);
-            // let res = introspect.sink_list().await.unwrap();
-            // dbg!(res);
-            let res = introspect.sink_input(199).await.unwrap();
-            dbg!(res);
+            let sink = context.introspect().sink_list().await.unwrap().remove(0);
+            assert_eq!(
+                context.introspect().sink(sink.index).await.unwrap().name,
+                sink.name
+            );
+
+            let mut playback = context
+                .crate_playback_stream(
+                    sink.index,
+                    48000,
+                    &sink.channel_map,
+                    Duration::from_millis(50),
+                )
+                .unwrap();
+            playback.writable().await.unwrap();
+
+            let inputs = context.introspect().sink_input_list().await.unwrap();
+            assert_eq!(inputs.len(), 1);
+
+            let input = context
+                .introspect()
+                .sink_input(inputs[0].index)
+                .await
+                .unwrap();
+            assert_eq!(input.sink, sink.index);
+            assert_eq!(input.channel_map.len(), sink.channel_map.len());
+
+            assert!(context
+                .introspect()
+                .sink_input(input.index + 1)
+                .await
+                .is_err());
+        });
+    }
+
+    #[test]
+    fn volume() {
+        let server = TestServer::spawn();
+
+        futures::executor::block_on(async {
+            let context = server.connect().await;
+
+            let sink = context.introspect().sink_list().await.unwrap().remove(0);
+
+            assert!(context.introspect().set_sink_volume(sink.index, 50.0).await);
+            let info = context.introspect().sink(sink.index).await.unwrap();
+            assert_eq!(info.volume.max(), Volume(Volume::NORMAL.0 / 2));
+
+            let mut playback = context
+                .crate_playback_stream(
+                    sink.index,
+                    48000,
+                    &sink.channel_map,
+                    Duration::from_millis(50),
+                )
+                .unwrap();
+            playback.writable().await.unwrap();
+
+            let input = context.introspect().sink_input_list().await.unwrap()[0].index;
+
+            assert!(
+                context
+                    .introspect()
+                    .set_sink_input_volume(input, 25.0)
+                    .await
+            );
+            let info = context.introspect().sink_input(input).await.unwrap();
+            assert_eq!(info.volume.max(), Volume(Volume::NORMAL.0 / 4));
+        });
+    }
+
+    #[test]
+    fn source() {
+        let server = TestServer::spawn();
+
+        futures::executor::block_on(async {
+            let context = server.connect().await;
+
+            let sink = context.introspect().sink_list().await.unwrap().remove(0);
+            let source = sink.monitor_source;
+
+            assert!(context.introspect().set_source_volume(source, 50.0).await);
+            assert!(context.introspect().set_source_mute(source, true).await);
+
+            let info = context.introspect().source(source).await.unwrap();
+            assert_eq!(info.volume.max(), Volume(Volume::NORMAL.0 / 2));
+            assert!(info.mute);
+
+            // Null sinks have no ports to switch to
+            assert!(
+                !context
+                    .introspect()
+                    .set_source_port(source, "no-such-port")
+                    .await
+            );
+        });
+    }
+
+    #[test]
+    fn source_output() {
+        let server = TestServer::spawn();
+
+        futures::executor::block_on(async {
+            let context = server.connect().await;
+
+            let sink = context.introspect().sink_list().await.unwrap().remove(0);
+
+            let mut record = context.crate_stream(sink.monitor_source, None, false);
+            record.next().await.unwrap();
+
+            let outputs = context.introspect().source_output_list().await.unwrap();
+            assert_eq!(outputs.len(), 1);
+            assert_eq!(outputs[0].source, sink.monitor_source);
+
+            let output = outputs[0].index;
+
+            // The peak stream is mono, percent volumes are stereo
+            let mut volume = ChannelVolumes::default();
+            volume.set(1, Volume(Volume::NORMAL.0 / 2));
+
+            assert!(
+                context
+                    .introspect()
+                    .set_source_output_volumes(output, &volume)
+                    .await
+            );
+            assert!(
+                context
+                    .introspect()
+                    .set_source_output_mute(output, true)
+                    .await
+            );
+
+            let info = context.introspect().source_output(output).await.unwrap();
+            assert_eq!(info.volume.max(), Volume(Volume::NORMAL.0 / 2));
+            assert!(info.mute);
+
+            assert!(context
+                .introspect()
+                .source_output(output + 1)
+                .await
+                .is_err());
         });
     }
 }
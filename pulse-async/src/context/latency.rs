@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+use futures::{
+    future::{select, Either},
+    StreamExt,
+};
+use pulse::{channelmap, error::PAErr};
+
+use crate::{
+    dsp::{chirp, find_delay},
+    Context,
+};
+
+const SAMPLE_RATE: u32 = 48000;
+
+/// Silence played before the chirp, gives both streams time to settle.
+const PRE_ROLL: Duration = Duration::from_millis(300);
+/// Longest round trip we are willing to wait for.
+const MAX_LATENCY: Duration = Duration::from_secs(2);
+/// Correlation below this means the chirp was not found in the recording.
+const MIN_CONFIDENCE: f32 = 0.3;
+
+#[derive(Debug, Clone, Copy)]
+pub struct LatencyMeasurement {
+    /// Time between writing the chirp to the sink and receiving it from the source.
+    pub round_trip: Duration,
+    /// Normalized correlation of the detected chirp, `0.0..=1.0`.
+    pub confidence: f32,
+}
+
+fn frames(duration: Duration) -> usize {
+    (duration.as_secs_f64() * SAMPLE_RATE as f64) as usize
+}
+
+/// Plays a chirp on `sink`, records `source` and cross-correlates both to find the round trip latency.
+///
+/// Needs the two to be connected, either with a loopback cable or a virtual null sink.
+/// Fails with [`pulse::error::Code::NoData`] when the chirp can't be found in the recording.
+pub async fn measure_latency(
+    context: &Context,
+    sink: u32,
+    source: u32,
+) -> Result<LatencyMeasurement, PAErr> {
+    let reference = chirp(SAMPLE_RATE, 0.1, 200.0, 8000.0);
+
+    let mut map = channelmap::Map::default();
+    map.init_mono();
+
+    // Unlike the level meters, the source must not be left suspended here
+    let mut record = super::stream::connect_pcm_stream(
+        context,
+        source,
+        None,
+        SAMPLE_RATE,
+        pulse::stream::FlagSet::DONT_MOVE | pulse::stream::FlagSet::ADJUST_LATENCY,
+    );
+    let mut playback = super::stream::crate_playback_stream(
+        context,
+        sink,
+        SAMPLE_RATE,
+        &map,
+        Duration::from_millis(20),
+    )?;
+
+    let pre_roll = frames(PRE_ROLL);
+    let max_latency = frames(MAX_LATENCY);
+
+    let mut recorded: Vec<f32> = Vec::new();
+    let mut written = 0;
+    let mut chirp_at: Option<usize> = None;
+
+    loop {
+        if let Some(at) = chirp_at {
+            if recorded.len() >= at + max_latency + reference.len() {
+                break;
+            }
+        }
+
+        // Source is not delivering anything, no point in waiting forever
+        if written > pre_roll + max_latency * 3 {
+            return Err(pulse::error::Code::NoData.into());
+        }
+
+        let event = match select(record.next(), Box::pin(playback.writable())).await {
+            Either::Left((samples, _)) => Either::Left(samples),
+            Either::Right((frames, _)) => Either::Right(frames),
+        };
+
+        match event {
+            Either::Left(Some(samples)) => recorded.extend(samples),
+            Either::Left(None) => return Err(pulse::error::Code::ConnectionTerminated.into()),
+            Either::Right(frames) => {
+                let frames = frames?;
+
+                let out: Vec<f32> = (written..written + frames)
+                    .map(|pos| {
+                        pos.checked_sub(pre_roll)
+                            .and_then(|i| reference.get(i))
+                            .copied()
+                            .unwrap_or(0.0)
+                    })
+                    .collect();
+
+                // Frames written ahead of the chirp in this chunk are not latency
+                if chirp_at.is_none() && written + frames > pre_roll {
+                    chirp_at = Some(recorded.len() + (pre_roll - written));
+                }
+
+                playback.write(&out)?;
+                written += frames;
+            }
+        }
+    }
+
+    let at = chirp_at.unwrap_or_default().min(recorded.len());
+
+    let (lag, confidence) =
+        find_delay(&reference, &recorded[at..]).ok_or(pulse::error::Code::NoData)?;
+
+    if confidence < MIN_CONFIDENCE {
+        return Err(pulse::error::Code::NoData.into());
+    }
+
+    Ok(LatencyMeasurement {
+        round_trip: Duration::from_secs_f64(lag as f64 / SAMPLE_RATE as f64),
+        confidence,
+    })
+}
@@ -1,10 +1,9 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{any::Any, cell::RefCell, rc::Rc, time::Duration};
 
-use futures::StreamExt;
-use libpulse_glib_binding::Mainloop;
+use futures::{channel::mpsc::UnboundedSender, StreamExt};
 use pulse::{
+    channelmap,
     context::{
-        self,
         subscribe::{Facility, InterestMaskSet, Operation},
         FlagSet, State,
     },
@@ -12,19 +11,60 @@ use pulse::{
     proplist::Proplist,
 };
 
+pub mod batch;
+mod debounce;
+pub mod device_manager;
+pub mod device_restore;
+pub mod fade;
 pub mod introspector;
+pub mod latency;
+mod operation;
 pub mod stream;
-
-use introspector::Introspector;
+pub mod stream_restore;
+pub mod test_signal;
+pub mod timing;
+
+use crate::dsp::{MeterConfig, MeterStream, Signal, SpectrumConfig, SpectrumStream};
+use crate::mainloop::MainloopBackend;
+use crate::volume::VolumeLevel;
+pub use batch::{Batch, BatchOp, BatchResults};
+pub use device_manager::DeviceManagerEntry;
+use device_manager::{DeviceManager, DeviceManagerEvents};
+pub use device_restore::DeviceFormats;
+use device_restore::{DeviceRestore, DeviceRestoreEvents};
+pub use fade::FadeCurve;
+use fade::{FadeTarget, Fades};
 pub use introspector::*;
+pub use stream_restore::StreamRestoreEntry;
+use stream_restore::{StreamRestore, StreamRestoreEvents};
+pub use timing::StreamStats;
 
 pub type ContextRc = Rc<RefCell<pulse::context::Context>>;
 
 pub type SubscribeEvent = Result<(Option<Facility>, Option<Operation>, u32), ()>;
 
+/// Sender of an event stream, closed on [`Context::disconnect`] so the stream ends.
+pub(crate) trait EventSender {
+    fn is_closed(&self) -> bool;
+    fn close(&self);
+}
+
+impl<T> EventSender for UnboundedSender<T> {
+    fn is_closed(&self) -> bool {
+        UnboundedSender::is_closed(self)
+    }
+
+    fn close(&self) {
+        self.close_channel();
+    }
+}
+
 pub struct Inner {
     pub context: pulse::context::Context,
-    _mainloop: Mainloop,
+    mainloop: Box<dyn MainloopBackend>,
+    fades: Fades,
+    operation_timeout: Option<Duration>,
+    event_senders: Vec<Box<dyn EventSender>>,
 }
 
 #[derive(Clone)]
@@ -41,38 +81,94 @@ impl std::fmt::Debug for Context {
     }
 }
 
+/// Holds the mainloop lock, released on drop.
+pub(crate) struct ContextLock<'a> {
+    context: &'a Context,
+}
+
+impl Drop for ContextLock<'_> {
+    fn drop(&mut self) {
+        self.context.inner.borrow_mut().mainloop.unlock();
+    }
+}
+
+/// Cancels a mainloop timer, the event has to be freed with the lock held.
+struct TimerGuard<'a> {
+    context: &'a Context,
+    timer: Option<Box<dyn Any>>,
+}
+
+impl Drop for TimerGuard<'_> {
+    fn drop(&mut self) {
+        let _lock = self.context.lock();
+        self.timer.take();
+    }
+}
+
 impl Context {
+    /// Creates a context driven by the GLib main context of the calling thread.
+    #[cfg(feature = "glib")]
     pub fn new_with_proplist(name: &str, proplist: &Proplist) -> Context {
-        let mainloop = Mainloop::new(None).expect("Failed to create mainloop");
+        let mainloop = crate::mainloop::GlibMainloop::new().expect("Failed to create mainloop");
+        Self::new_with_mainloop(mainloop, name, proplist)
+    }
 
-        let context = context::Context::new_with_proplist(&mainloop, name, proplist)
+    /// Creates a context driven by its own mainloop thread, usable from any async runtime.
+    pub fn new_threaded_with_proplist(name: &str, proplist: &Proplist) -> Context {
+        let mainloop = crate::mainloop::ThreadedMainloop::new().expect("Failed to create mainloop");
+        Self::new_with_mainloop(mainloop, name, proplist)
+    }
+
+    pub fn new_with_mainloop<M>(mut mainloop: M, name: &str, proplist: &Proplist) -> Context
+    where
+        M: MainloopBackend + 'static,
+    {
+        let context = mainloop
+            .new_context(name, proplist)
             .expect("Failed to create new context");
 
         Self {
             inner: Rc::new(RefCell::new(Inner {
                 context,
-                _mainloop: mainloop,
+                mainloop: Box::new(mainloop),
+                fades: Fades::default(),
+                operation_timeout: None,
+                event_senders: Vec::new(),
             })),
         }
     }
 
+    /// Locks the mainloop, has to be held while touching any libpulse object of this context.
+    pub(crate) fn lock(&self) -> ContextLock<'_> {
+        self.inner.borrow_mut().mainloop.lock();
+        ContextLock { context: self }
+    }
+
     pub async fn connect(&self, server: Option<&str>, flags: FlagSet) -> Result<(), PAErr> {
         let (mut tx, mut rx) = futures::channel::mpsc::unbounded::<()>();
 
-        self.inner
-            .borrow_mut()
-            .context
-            .set_state_callback(Some(Box::new(move || {
-                tx.start_send(()).ok();
-            })));
+        {
+            let _lock = self.lock();
 
-        self.inner
-            .borrow_mut()
-            .context
-            .connect(server, flags, None)?;
+            self.inner
+                .borrow_mut()
+                .context
+                .set_state_callback(Some(Box::new(move || {
+                    tx.start_send(()).ok();
+                })));
+
+            self.inner
+                .borrow_mut()
+                .context
+                .connect(server, flags, None)?;
+        }
 
         while rx.next().await.is_some() {
-            match self.inner.borrow_mut().context.get_state() {
+            let _lock = self.lock();
+            let state = self.inner.borrow().context.get_state();
+            drop(_lock);
+
+            match state {
                 State::Ready => {
                     return Ok(());
                 }
@@ -89,12 +185,48 @@ impl Context {
         Err(pulse::error::Code::Unknown.into())
     }
 
+    /// Name of the server this context is connected to.
+    pub fn server(&self) -> Option<String> {
+        let _lock = self.lock();
+        let inner = self.inner.borrow();
+
+        inner.context.get_server()
+    }
+
+    /// Disconnects from the server and ends all event streams of this context.
     pub fn disconnect(&mut self) {
-        self.inner.borrow_mut().context.disconnect();
+        let _lock = self.lock();
+        let mut inner = self.inner.borrow_mut();
+
+        inner.context.set_subscribe_callback(None);
+        inner.context.disconnect();
+
+        // Extension callbacks belong to their event streams, which drop them once they end
+        for tx in inner.event_senders.drain(..) {
+            tx.close();
+        }
+    }
+
+    /// Whether the context is connected, libpulse refuses operations otherwise.
+    ///
+    /// Has to be called with the lock held.
+    pub(crate) fn is_ready(&self) -> bool {
+        self.inner.borrow().context.get_state() == State::Ready
+    }
+
+    /// Ends the stream fed by `tx` once the context disconnects.
+    pub(crate) fn close_on_disconnect<T: 'static>(&self, tx: UnboundedSender<T>) {
+        let mut inner = self.inner.borrow_mut();
+
+        inner.event_senders.retain(|tx| !tx.is_closed());
+        inner.event_senders.push(Box::new(tx));
     }
 
     pub fn introspect(&self) -> Introspector<'_> {
-        Introspector::from(self.inner.borrow().context.introspect())
+        let _lock = self.lock();
+        let introspector = self.inner.borrow().context.introspect();
+
+        Introspector::new(self, introspector)
     }
 
     pub fn subscribe(&self, mask: InterestMaskSet) -> impl futures::Stream<Item = SubscribeEvent> {
@@ -107,6 +239,10 @@ impl Context {
             }
         });
 
+        self.close_on_disconnect(tx.clone());
+
+        let _lock = self.lock();
+
         self.inner
             .borrow_mut()
             .context
@@ -124,46 +260,330 @@ impl Context {
         rx
     }
 
-    pub fn crate_stream(&self, id: u32, stream_id: Option<u32>) -> stream::Stream {
-        super::stream::crate_stream(self, id, stream_id)
+    /// Like [`Context::subscribe`], but holds events back for `window` after the first one of a
+    /// burst, yielding one event per object and dropping objects that came and went.
+    pub fn subscribe_debounced(
+        &self,
+        mask: InterestMaskSet,
+        window: Duration,
+    ) -> impl futures::Stream<Item = SubscribeEvent> + Unpin {
+        debounce::debounce(self.clone(), self.subscribe(mask), window)
+    }
+
+    /// Starts collecting operations to send at once.
+    pub fn batch(&self) -> Batch<'_> {
+        Batch::new(self)
+    }
+
+    /// Reads and edits what module-stream-restore remembers about streams.
+    pub fn stream_restore(&self) -> StreamRestore<'_> {
+        StreamRestore::new(self)
+    }
+
+    /// Yields each time an entry of module-stream-restore changes.
+    pub fn subscribe_stream_restore(&self) -> StreamRestoreEvents {
+        StreamRestoreEvents::new(self)
+    }
+
+    /// Lists known devices and edits their role priorities, see [`device_manager`].
+    pub fn device_manager(&self) -> DeviceManager<'_> {
+        DeviceManager::new(self)
+    }
+
+    /// Yields each time a device known to module-device-manager changes.
+    pub fn subscribe_device_manager(&self) -> DeviceManagerEvents {
+        DeviceManagerEvents::new(self)
+    }
+
+    /// Reads and saves the formats devices support, see [`device_restore`].
+    pub fn device_restore(&self) -> DeviceRestore<'_> {
+        DeviceRestore::new(self)
+    }
+
+    /// Yields the devices whose data saved by module-device-restore changes.
+    pub fn subscribe_device_restore(&self) -> DeviceRestoreEvents {
+        DeviceRestoreEvents::new(self)
+    }
+
+    /// Resolves once `duration` has elapsed, timed by the mainloop.
+    pub async fn sleep(&self, duration: Duration) {
+        let (tx, rx) = futures::channel::oneshot::channel::<()>();
+
+        let mut tx = Some(tx);
+        let callback = Box::new(move || {
+            if let Some(tx) = tx.take() {
+                tx.send(()).ok();
+            }
+        });
+
+        let timer = {
+            let _lock = self.lock();
+            let mut inner = self.inner.borrow_mut();
+            let inner = &mut *inner;
+
+            inner.mainloop.timer(&inner.context, duration, callback)
+        };
+
+        match timer {
+            Some(timer) => {
+                let _guard = TimerGuard {
+                    context: self,
+                    timer: Some(timer),
+                };
+                rx.await.ok();
+            }
+            None => {
+                let (tx, rx) = futures::channel::oneshot::channel::<()>();
+                std::thread::spawn(move || {
+                    std::thread::sleep(duration);
+                    tx.send(()).ok();
+                });
+                rx.await.ok();
+            }
+        }
+    }
+
+    /// Fades the volume of a sink to `target` over `duration`, keeping balance and fade.
+    ///
+    /// Resolves with `Ok(false)` if a newer fade of the same sink took over, fails if the sink went
+    /// away.
+    pub async fn fade_sink_volume(
+        &self,
+        id: u32,
+        target: VolumeLevel,
+        duration: Duration,
+        curve: FadeCurve,
+    ) -> Result<bool, ()> {
+        fade::fade(self, FadeTarget::Sink(id), target, duration, curve).await
+    }
+
+    /// Fades the volume of a source to `target` over `duration`, see [`Context::fade_sink_volume`].
+    pub async fn fade_source_volume(
+        &self,
+        id: u32,
+        target: VolumeLevel,
+        duration: Duration,
+        curve: FadeCurve,
+    ) -> Result<bool, ()> {
+        fade::fade(self, FadeTarget::Source(id), target, duration, curve).await
+    }
+
+    /// Fades the volume of a stream to `target` over `duration`, see [`Context::fade_sink_volume`].
+    pub async fn fade_sink_input_volume(
+        &self,
+        id: u32,
+        target: VolumeLevel,
+        duration: Duration,
+        curve: FadeCurve,
+    ) -> Result<bool, ()> {
+        fade::fade(self, FadeTarget::SinkInput(id), target, duration, curve).await
+    }
+
+    /// See [`stream::crate_stream`].
+    pub fn crate_stream(
+        &self,
+        id: u32,
+        stream_id: Option<u32>,
+        timing_updates: bool,
+    ) -> stream::Stream {
+        super::stream::crate_stream(self, id, stream_id, timing_updates)
+    }
+
+    /// See [`stream::crate_pcm_stream`].
+    pub fn crate_pcm_stream(
+        &self,
+        id: u32,
+        stream_id: Option<u32>,
+        rate: u32,
+        timing_updates: bool,
+    ) -> stream::PcmStream {
+        super::stream::crate_pcm_stream(self, id, stream_id, rate, timing_updates)
+    }
+
+    pub fn crate_spectrum_stream(
+        &self,
+        id: u32,
+        stream_id: Option<u32>,
+        config: SpectrumConfig,
+    ) -> SpectrumStream {
+        let pcm = self.crate_pcm_stream(id, stream_id, config.sample_rate, false);
+        SpectrumStream::new(pcm, config)
+    }
+
+    pub fn crate_meter_stream(
+        &self,
+        id: u32,
+        stream_id: Option<u32>,
+        config: MeterConfig,
+    ) -> MeterStream {
+        let pcm = self.crate_pcm_stream(id, stream_id, config.sample_rate, false);
+        MeterStream::new(pcm, config)
+    }
+
+    pub fn crate_playback_stream(
+        &self,
+        id: u32,
+        rate: u32,
+        channel_map: &channelmap::Map,
+        latency: std::time::Duration,
+    ) -> Result<stream::PlaybackStream, PAErr> {
+        super::stream::crate_playback_stream(self, id, rate, channel_map, latency)
+    }
+
+    /// Measures the round trip latency between a sink and a source, see [`latency::measure_latency`].
+    pub async fn measure_latency(
+        &self,
+        sink: u32,
+        source: u32,
+    ) -> Result<latency::LatencyMeasurement, PAErr> {
+        latency::measure_latency(self, sink, source).await
+    }
+
+    /// Plays a test signal on each channel of a sink in turn, see [`test_signal::play_test_signal`].
+    pub async fn play_test_signal<F>(
+        &self,
+        id: u32,
+        channel_map: &channelmap::Map,
+        signal: Signal,
+        duration: std::time::Duration,
+        on_channel: F,
+    ) -> Result<(), PAErr>
+    where
+        F: FnMut(usize, channelmap::Position),
+    {
+        test_signal::play_test_signal(self, id, channel_map, signal, duration, on_channel).await
     }
 }
 
 impl Drop for Inner {
     fn drop(&mut self) {
+        self.mainloop.lock();
+        self.context.set_state_callback(None);
         self.context.set_subscribe_callback(None);
         self.context.disconnect();
+        self.mainloop.unlock();
+
+        self.mainloop.shutdown();
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
+    use futures::future::{select, Either};
+
     use super::*;
+    use crate::test_server::TestServer;
 
     #[test]
     fn context() {
-        let props = Proplist::new().unwrap();
-        let context = Context::new_with_proplist("Test", &props);
+        let server = TestServer::spawn();
+
+        futures::executor::block_on(async {
+            let context = server.connect().await;
 
-        glib::MainContext::default().block_on(async move {
-            context.connect(None, FlagSet::NOFLAGS).await.unwrap();
+            let _lock = context.lock();
+            let state = context.inner.borrow().context.get_state();
+            assert_eq!(state, State::Ready);
         });
     }
 
     #[test]
-    #[ignore]
     fn subscribe() {
-        let props = Proplist::new().unwrap();
-        let context = Context::new_with_proplist("Test", &props);
+        let server = TestServer::spawn();
+
+        futures::executor::block_on(async {
+            let context = server.connect().await;
+            let mut events = context.subscribe(InterestMaskSet::SINK_INPUT);
+
+            let sink = context.introspect().sink_list().await.unwrap().remove(0);
+            let mut playback = context
+                .crate_playback_stream(
+                    sink.index,
+                    48000,
+                    &sink.channel_map,
+                    Duration::from_millis(50),
+                )
+                .unwrap();
+            playback.writable().await.unwrap();
+
+            let (facility, operation, index) = events.next().await.unwrap().unwrap();
+            assert_eq!(facility, Some(Facility::SinkInput));
+            assert_eq!(operation, Some(Operation::New));
+
+            drop(playback);
+
+            loop {
+                let (facility, operation, id) = events.next().await.unwrap().unwrap();
+                assert_eq!(facility, Some(Facility::SinkInput));
+                assert_eq!(id, index);
+
+                if operation == Some(Operation::Removed) {
+                    break;
+                }
+            }
+        });
+    }
 
-        glib::MainContext::default().block_on(async move {
-            context.connect(None, FlagSet::NOFLAGS).await.unwrap();
+    #[test]
+    fn disconnect() {
+        let server = TestServer::spawn();
 
-            let mut stream = context.subscribe(InterestMaskSet::SINK_INPUT);
+        futures::executor::block_on(async {
+            let mut context = server.connect().await;
+            let mut events = context.subscribe(InterestMaskSet::SINK);
+            let mut restore = context.subscribe_stream_restore();
 
-            while let Some(event) = stream.next().await {
-                let (facility, operation, index) = event.unwrap();
-                dbg!(facility, operation, index);
+            context.disconnect();
+
+            assert!(events.next().await.is_none());
+            assert!(restore.next().await.is_none());
+        });
+    }
+
+    #[test]
+    fn monitor_stream() {
+        let server = TestServer::spawn();
+
+        futures::executor::block_on(async {
+            let context = server.connect().await;
+            let sink = context.introspect().sink_list().await.unwrap().remove(0);
+
+            let mut map = channelmap::Map::default();
+            map.init_mono();
+
+            let mut record = context.crate_pcm_stream(sink.monitor_source, None, 48000, false);
+            let mut playback = context
+                .crate_playback_stream(sink.index, 48000, &map, Duration::from_millis(50))
+                .unwrap();
+
+            let mut generator = crate::dsp::Generator::new(Signal::Sine { freq: 1000.0 }, 48000);
+            let mut received = 0;
+
+            loop {
+                let event = match select(record.next(), Box::pin(playback.writable())).await {
+                    Either::Left((samples, _)) => Either::Left(samples),
+                    Either::Right((frames, _)) => Either::Right(frames),
+                };
+
+                match event {
+                    Either::Left(samples) => {
+                        let samples = samples.expect("Monitor stream ended");
+
+                        if samples.iter().any(|v| v.abs() > 0.1) {
+                            break;
+                        }
+
+                        received += samples.len();
+                        assert!(received < 48000 * 5, "Monitor stayed silent");
+                    }
+                    Either::Right(frames) => {
+                        let mut out = vec![0.0; frames.unwrap()];
+                        generator.fill(&mut out);
+                        playback.write(&out).unwrap();
+                    }
+                }
             }
         });
     }
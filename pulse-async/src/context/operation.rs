@@ -0,0 +1,107 @@
+//! Waiting on libpulse operations, giving up on them after a timeout or when the waiting future
+//! is dropped.
+
+use std::time::Duration;
+
+use futures::{
+    channel::oneshot,
+    future::{self, Either},
+};
+use pulse::operation::{self, Operation};
+
+use super::Context;
+
+/// Cancels a still running operation when dropped, so its callback is never called.
+struct OperationGuard<'a, C: ?Sized> {
+    context: &'a Context,
+    operation: Option<Operation<C>>,
+}
+
+impl<C: ?Sized> Drop for OperationGuard<'_, C> {
+    fn drop(&mut self) {
+        let _lock = self.context.lock();
+
+        if let Some(mut operation) = self.operation.take() {
+            if operation.get_state() == operation::State::Running {
+                operation.cancel();
+            }
+        }
+    }
+}
+
+impl Context {
+    /// Default timeout of operations, `None` waits forever.
+    pub fn operation_timeout(&self) -> Option<Duration> {
+        self.inner.borrow().operation_timeout
+    }
+
+    /// Sets how long operations wait for the server before failing, `None` waits forever.
+    ///
+    /// Only affects operations started afterwards, see also [`Introspector::with_timeout`].
+    ///
+    /// [`Introspector::with_timeout`]: super::Introspector::with_timeout
+    pub fn set_operation_timeout(&self, timeout: Option<Duration>) {
+        self.inner.borrow_mut().operation_timeout = timeout;
+    }
+
+    /// Waits for the result `operation` sends through `rx`.
+    ///
+    /// Resolves with `None` if the operation failed to answer in time, cancelling it like dropping
+    /// the returned future does.
+    pub(crate) async fn wait<T, C: ?Sized>(
+        &self,
+        operation: Operation<C>,
+        rx: oneshot::Receiver<T>,
+        timeout: Option<Duration>,
+    ) -> Option<T> {
+        let _guard = OperationGuard {
+            context: self,
+            operation: Some(operation),
+        };
+
+        match timeout {
+            Some(timeout) => {
+                let sleep = self.sleep(timeout);
+                futures::pin_mut!(sleep);
+
+                match future::select(rx, sleep).await {
+                    Either::Left((res, _)) => res.ok(),
+                    Either::Right(_) => None,
+                }
+            }
+            None => rx.await.ok(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::FutureExt;
+
+    use crate::test_server::TestServer;
+
+    #[test]
+    fn timeout_and_cancel() {
+        let server = TestServer::spawn();
+
+        futures::executor::block_on(async {
+            let context = server.connect().await;
+
+            // Polling once sends the request, dropping the future afterwards cancels it
+            let introspect = context.introspect();
+            let mut list = Box::pin(introspect.sink_list());
+            assert!((&mut list).now_or_never().is_none());
+            drop(list);
+
+            // Nothing answers that fast
+            let introspect = context.introspect().with_timeout(Some(Duration::ZERO));
+            assert!(introspect.sink_list().await.is_err());
+
+            context.set_operation_timeout(Some(Duration::from_secs(5)));
+            assert_eq!(context.operation_timeout(), Some(Duration::from_secs(5)));
+            assert!(context.introspect().sink_list().await.is_ok());
+        });
+    }
+}
@@ -1,9 +1,32 @@
-use futures::{channel::mpsc::UnboundedReceiver, pin_mut};
+use futures::{channel::mpsc::UnboundedReceiver, pin_mut, StreamExt};
+use pulse::channelmap;
 use pulse::def::BufferAttr;
+use pulse::error::PAErr;
+use pulse::stream::{PeekResult, SeekMode, State};
 
+use super::timing::{impl_timing, Timing};
 use crate::Context;
 
-pub fn crate_stream(context: &Context, id: u32, stream_id: Option<u32>) -> Stream {
+/// Flags asking the server to push timing info, see [`Stream::next_timing_update`].
+fn timing_flags(timing_updates: bool) -> pulse::stream::FlagSet {
+    if timing_updates {
+        pulse::stream::FlagSet::AUTO_TIMING_UPDATE | pulse::stream::FlagSet::INTERPOLATE_TIMING
+    } else {
+        pulse::stream::FlagSet::NOFLAGS
+    }
+}
+
+/// Creates a stream of peak values.
+///
+/// With `timing_updates` the server pushes timing info, level meters have no use for it.
+pub fn crate_stream(
+    context: &Context,
+    id: u32,
+    stream_id: Option<u32>,
+    timing_updates: bool,
+) -> Stream {
+    let _lock = context.lock();
+
     let mut stream = pulse::stream::Stream::new(
         &mut context.inner.borrow_mut().context,
         "Stream Monitor",
@@ -26,6 +49,8 @@ pub fn crate_stream(context: &Context, id: u32, stream_id: Option<u32>) -> Strea
         stream.set_monitor_stream(stream_id).ok();
     }
 
+    let timing = Timing::attach(&mut stream);
+
     stream
         .connect_record(
             Some(&format!("{}", id)),
@@ -37,18 +62,28 @@ pub fn crate_stream(context: &Context, id: u32, stream_id: Option<u32>) -> Strea
             pulse::stream::FlagSet::DONT_MOVE
                 | pulse::stream::FlagSet::PEAK_DETECT
                 | pulse::stream::FlagSet::ADJUST_LATENCY
-                | pulse::stream::FlagSet::DONT_INHIBIT_AUTO_SUSPEND,
+                | pulse::stream::FlagSet::DONT_INHIBIT_AUTO_SUSPEND
+                | timing_flags(timing_updates),
         )
         .unwrap();
 
-    Stream { rx, stream }
+    Stream {
+        rx,
+        stream,
+        timing,
+        context: context.clone(),
+    }
 }
 
 pub struct Stream {
     rx: UnboundedReceiver<usize>,
     stream: pulse::stream::Stream,
+    timing: Timing,
+    context: Context,
 }
 
+impl_timing!(Stream);
+
 impl futures::Stream for Stream {
     type Item = f32;
 
@@ -56,14 +91,17 @@ impl futures::Stream for Stream {
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
-        let rx = &mut self.rx;
+        let this = &mut *self;
+
+        let rx = &mut this.rx;
         pin_mut!(rx);
         let pool = futures::Stream::poll_next(rx, cx);
 
         match pool {
             std::task::Poll::Ready(len) => {
                 if let Some(len) = len {
-                    let data = self.stream.peek().unwrap();
+                    let _lock = this.context.lock();
+                    let data = this.stream.peek().unwrap();
 
                     let v = match data {
                         pulse::stream::PeekResult::Empty => None,
@@ -76,7 +114,7 @@ impl futures::Stream for Stream {
                     };
 
                     if len != 0 {
-                        self.stream.discard().unwrap();
+                        this.stream.discard().unwrap();
                     }
 
                     if let Some(v) = v {
@@ -95,7 +133,293 @@ impl futures::Stream for Stream {
 
 impl Drop for Stream {
     fn drop(&mut self) {
+        let _lock = self.context.lock();
+        self.stream.set_read_callback(None);
+        Timing::detach(&mut self.stream);
+    }
+}
+
+/// Creates a mono `F32le` record stream that yields raw PCM fragments instead of peaks.
+///
+/// `timing_updates` works as for [`crate_stream`].
+pub fn crate_pcm_stream(
+    context: &Context,
+    id: u32,
+    stream_id: Option<u32>,
+    rate: u32,
+    timing_updates: bool,
+) -> PcmStream {
+    connect_pcm_stream(
+        context,
+        id,
+        stream_id,
+        rate,
+        pulse::stream::FlagSet::DONT_MOVE
+            | pulse::stream::FlagSet::ADJUST_LATENCY
+            | pulse::stream::FlagSet::DONT_INHIBIT_AUTO_SUSPEND
+            | timing_flags(timing_updates),
+    )
+}
+
+pub(crate) fn connect_pcm_stream(
+    context: &Context,
+    id: u32,
+    stream_id: Option<u32>,
+    rate: u32,
+    flags: pulse::stream::FlagSet,
+) -> PcmStream {
+    let _lock = context.lock();
+
+    let mut stream = pulse::stream::Stream::new(
+        &mut context.inner.borrow_mut().context,
+        "PCM Monitor",
+        &pulse::sample::Spec {
+            format: pulse::sample::Format::F32le,
+            rate,
+            channels: 1,
+        },
+        None,
+    )
+    .unwrap();
+
+    let (tx, rx) = futures::channel::mpsc::unbounded::<usize>();
+
+    stream.set_read_callback(Some(Box::new(move |len| {
+        tx.unbounded_send(len).ok();
+    })));
+
+    if let Some(stream_id) = stream_id {
+        stream.set_monitor_stream(stream_id).ok();
+    }
+
+    let timing = Timing::attach(&mut stream);
+
+    // Ask for roughly 20ms worth of samples per fragment
+    let fragsize = (rate / 50).max(1) * std::mem::size_of::<f32>() as u32;
+
+    stream
+        .connect_record(
+            Some(&format!("{}", id)),
+            Some(&BufferAttr {
+                fragsize,
+                maxlength: u32::MAX,
+                ..Default::default()
+            }),
+            flags,
+        )
+        .unwrap();
+
+    PcmStream {
+        rx,
+        stream,
+        timing,
+        context: context.clone(),
+    }
+}
+
+/// Record stream yielding chunks of mono `f32` samples.
+pub struct PcmStream {
+    rx: UnboundedReceiver<usize>,
+    stream: pulse::stream::Stream,
+    timing: Timing,
+    context: Context,
+}
+
+impl_timing!(PcmStream);
+
+impl futures::Stream for PcmStream {
+    type Item = Vec<f32>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        loop {
+            match self.rx.poll_next_unpin(cx) {
+                std::task::Poll::Ready(Some(_)) => {
+                    let this = &mut *self;
+                    let _lock = this.context.lock();
+
+                    let samples = match this.stream.peek() {
+                        Ok(PeekResult::Data(data)) => Some(
+                            data.chunks_exact(4)
+                                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                                .collect::<Vec<f32>>(),
+                        ),
+                        Ok(PeekResult::Hole(_)) => None,
+                        Ok(PeekResult::Empty) | Err(_) => continue,
+                    };
+
+                    this.stream.discard().ok();
+
+                    if let Some(samples) = samples {
+                        return std::task::Poll::Ready(Some(samples));
+                    }
+                }
+                std::task::Poll::Ready(None) => return std::task::Poll::Ready(None),
+                std::task::Poll::Pending => return std::task::Poll::Pending,
+            }
+        }
+    }
+}
+
+impl Drop for PcmStream {
+    fn drop(&mut self) {
+        let _lock = self.context.lock();
         self.stream.set_read_callback(None);
+        Timing::detach(&mut self.stream);
+        self.stream.disconnect().ok();
+    }
+}
+
+/// Creates a `F32le` playback stream on the given sink.
+///
+/// `latency` is the target buffer length, short buffers keep written audio close to what is heard.
+pub fn crate_playback_stream(
+    context: &Context,
+    id: u32,
+    rate: u32,
+    channel_map: &channelmap::Map,
+    latency: std::time::Duration,
+) -> Result<PlaybackStream, PAErr> {
+    let spec = pulse::sample::Spec {
+        format: pulse::sample::Format::F32le,
+        rate,
+        channels: channel_map.len(),
+    };
+
+    let _lock = context.lock();
+
+    let mut stream = pulse::stream::Stream::new(
+        &mut context.inner.borrow_mut().context,
+        "Playback",
+        &spec,
+        Some(channel_map),
+    )
+    .ok_or(PAErr::from(pulse::error::Code::Invalid))?;
+
+    let (tx, rx) = futures::channel::mpsc::unbounded::<()>();
+
+    stream.set_write_callback(Some(Box::new({
+        let tx = tx.clone();
+        move |_| {
+            tx.unbounded_send(()).ok();
+        }
+    })));
+
+    stream.set_state_callback(Some(Box::new(move || {
+        tx.unbounded_send(()).ok();
+    })));
+
+    let timing = Timing::attach(&mut stream);
+
+    let tlength = spec.usec_to_bytes(pulse::time::MicroSeconds(latency.as_micros() as u64));
+
+    stream.connect_playback(
+        Some(&format!("{}", id)),
+        Some(&BufferAttr {
+            maxlength: u32::MAX,
+            tlength: tlength as u32,
+            prebuf: u32::MAX,
+            minreq: u32::MAX,
+            fragsize: u32::MAX,
+        }),
+        pulse::stream::FlagSet::DONT_MOVE
+            | pulse::stream::FlagSet::ADJUST_LATENCY
+            | pulse::stream::FlagSet::AUTO_TIMING_UPDATE
+            | pulse::stream::FlagSet::INTERPOLATE_TIMING,
+        None,
+        None,
+    )?;
+
+    Ok(PlaybackStream {
+        rx,
+        stream,
+        timing,
+        context: context.clone(),
+        channels: spec.channels as usize,
+    })
+}
+
+/// Playback stream accepting interleaved `f32` frames.
+pub struct PlaybackStream {
+    rx: UnboundedReceiver<()>,
+    stream: pulse::stream::Stream,
+    timing: Timing,
+    context: Context,
+    channels: usize,
+}
+
+impl_timing!(PlaybackStream);
+
+impl PlaybackStream {
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Waits until the server wants more data, resolves with the number of frames that can be written.
+    pub async fn writable(&mut self) -> Result<usize, PAErr> {
+        loop {
+            let _lock = self.context.lock();
+
+            match self.stream.get_state() {
+                State::Ready => {
+                    let bytes = self.stream.writable_size().unwrap_or(0);
+                    let frames = bytes / (self.channels * std::mem::size_of::<f32>());
+
+                    if frames > 0 {
+                        return Ok(frames);
+                    }
+                }
+                State::Failed => return Err(pulse::error::Code::Unknown.into()),
+                State::Terminated => return Err(pulse::error::Code::ConnectionTerminated.into()),
+                _ => {}
+            }
+
+            drop(_lock);
+
+            if self.rx.next().await.is_none() {
+                return Err(pulse::error::Code::ConnectionTerminated.into());
+            }
+        }
+    }
+
+    /// Writes interleaved samples.
+    pub fn write(&mut self, samples: &[f32]) -> Result<(), PAErr> {
+        let bytes: Vec<u8> = samples.iter().flat_map(|v| v.to_le_bytes()).collect();
+
+        let _lock = self.context.lock();
+        self.stream.write_copy(&bytes, 0, SeekMode::Relative)
+    }
+
+    /// Waits for the playback buffer to be played out.
+    pub async fn drain(&mut self) -> bool {
+        let (tx, rx) = futures::channel::oneshot::channel::<bool>();
+
+        let mut tx = Some(tx);
+        let operation = {
+            let _lock = self.context.lock();
+            self.stream.drain(Some(Box::new(move |success| {
+                if let Some(tx) = tx.take() {
+                    tx.send(success).ok();
+                }
+            })))
+        };
+
+        self.context
+            .wait(operation, rx, self.context.operation_timeout())
+            .await
+            .unwrap_or(false)
+    }
+}
+
+impl Drop for PlaybackStream {
+    fn drop(&mut self) {
+        let _lock = self.context.lock();
+        self.stream.set_write_callback(None);
+        self.stream.set_state_callback(None);
+        Timing::detach(&mut self.stream);
+        self.stream.disconnect().ok();
     }
 }
 
@@ -0,0 +1,268 @@
+//! Access to the entries remembered by `module-stream-restore`.
+//!
+//! Entries are keyed by names like `sink-input-by-application-name:Firefox` or
+//! `sink-input-by-media-role:event` (system sounds), and hold the volume, mute state and device
+//! new matching streams get.
+
+use std::borrow::Cow;
+
+use futures::{channel::mpsc::UnboundedReceiver, StreamExt};
+use pulse::{
+    channelmap,
+    context::ext_stream_restore::{self, Info},
+    proplist::UpdateMode,
+    volume::ChannelVolumes,
+};
+
+use super::{introspector::list_callback, Context};
+
+/// Entry name module-stream-restore uses for event sounds (`media.role` `event`).
+pub const EVENT_SOUNDS: &str = "sink-input-by-media-role:event";
+
+/// A remembered stream.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StreamRestoreEntry {
+    /// Key the entry matches streams by.
+    pub name: String,
+    /// Channel map of `volume`, empty if no volume is remembered.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::channel_map"))]
+    pub channel_map: channelmap::Map,
+    /// Remembered volume.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::channel_volumes"))]
+    pub volume: ChannelVolumes,
+    /// Name of the remembered sink or source.
+    pub device: Option<String>,
+    /// Remembered mute state.
+    pub mute: bool,
+}
+
+impl<'a> From<&'a Info<'a>> for StreamRestoreEntry {
+    fn from(item: &'a Info<'a>) -> Self {
+        Self {
+            name: item
+                .name
+                .as_ref()
+                .map(|cow| cow.to_string())
+                .unwrap_or_default(),
+            channel_map: item.channel_map,
+            volume: item.volume,
+            device: item.device.as_ref().map(|cow| cow.to_string()),
+            mute: item.mute,
+        }
+    }
+}
+
+impl StreamRestoreEntry {
+    fn info(&self) -> Info<'_> {
+        Info {
+            name: Some(Cow::Borrowed(self.name.as_str())),
+            channel_map: self.channel_map,
+            volume: self.volume,
+            device: self.device.as_deref().map(Cow::Borrowed),
+            mute: self.mute,
+        }
+    }
+
+    /// Splits the name into what it matches, e.g. `sink-input-by-application-name`, and the value.
+    pub fn key(&self) -> Option<(&str, &str)> {
+        self.name.split_once(':')
+    }
+
+    /// Whether this entry applies to recording streams.
+    pub fn is_source_output(&self) -> bool {
+        self.name.starts_with("source-output-")
+    }
+}
+
+pub struct StreamRestore<'a> {
+    ext: ext_stream_restore::StreamRestore,
+    context: &'a Context,
+}
+
+impl<'a> StreamRestore<'a> {
+    pub(crate) fn new(context: &'a Context) -> Self {
+        let _lock = context.lock();
+        let ext = context.inner.borrow().context.stream_restore();
+
+        Self { ext, context }
+    }
+
+    /// Gets all remembered entries.
+    pub async fn read(&mut self) -> Result<Vec<StreamRestoreEntry>, ()> {
+        let (tx, rx) = futures::channel::oneshot::channel();
+
+        let operation = {
+            let _lock = self.context.lock();
+            self.ext.read(list_callback!(tx, StreamRestoreEntry::from))
+        };
+
+        self.context
+            .wait(operation, rx, self.context.operation_timeout())
+            .await
+            .unwrap_or(Err(()))
+    }
+
+    /// Stores `entries`.
+    ///
+    /// [`UpdateMode::Set`] forgets all other entries, [`UpdateMode::Merge`] leaves existing entries
+    /// of the same name alone and [`UpdateMode::Replace`] overwrites them.
+    /// With `apply_immediately` running streams matching the entries are updated too.
+    pub async fn write(
+        &mut self,
+        entries: &[StreamRestoreEntry],
+        mode: UpdateMode,
+        apply_immediately: bool,
+    ) -> bool {
+        let infos: Vec<Info> = entries.iter().map(StreamRestoreEntry::info).collect();
+        let infos: Vec<&Info> = infos.iter().collect();
+
+        let (tx, rx) = futures::channel::oneshot::channel::<bool>();
+
+        let mut tx = Some(tx);
+        let operation = {
+            let _lock = self.context.lock();
+            self.ext
+                .write(mode, &infos, apply_immediately, move |success| {
+                    if let Some(tx) = tx.take() {
+                        tx.send(success).ok();
+                    }
+                })
+        };
+
+        self.context
+            .wait(operation, rx, self.context.operation_timeout())
+            .await
+            .unwrap_or(false)
+    }
+
+    /// Forgets the entries named `names`.
+    pub async fn delete(&mut self, names: &[&str]) -> bool {
+        let (tx, rx) = futures::channel::oneshot::channel::<bool>();
+
+        let mut tx = Some(tx);
+        let operation = {
+            let _lock = self.context.lock();
+            self.ext.delete(names, move |success| {
+                if let Some(tx) = tx.take() {
+                    tx.send(success).ok();
+                }
+            })
+        };
+
+        self.context
+            .wait(operation, rx, self.context.operation_timeout())
+            .await
+            .unwrap_or(false)
+    }
+}
+
+/// Yields each time the remembered entries change, see [`Context::subscribe_stream_restore`].
+pub struct StreamRestoreEvents {
+    rx: UnboundedReceiver<()>,
+    ext: ext_stream_restore::StreamRestore,
+    context: Context,
+}
+
+impl StreamRestoreEvents {
+    pub(crate) fn new(context: &Context) -> Self {
+        let _lock = context.lock();
+        let mut ext = context.inner.borrow().context.stream_restore();
+
+        let (tx, rx) = futures::channel::mpsc::unbounded::<()>();
+        context.close_on_disconnect(tx.clone());
+
+        ext.set_subscribe_cb(move || {
+            tx.unbounded_send(()).ok();
+        });
+        ext.subscribe(true, |_| {});
+
+        Self {
+            rx,
+            ext,
+            context: context.clone(),
+        }
+    }
+}
+
+impl futures::Stream for StreamRestoreEvents {
+    type Item = ();
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.rx.poll_next_unpin(cx)
+    }
+}
+
+impl Drop for StreamRestoreEvents {
+    fn drop(&mut self) {
+        let _lock = self.context.lock();
+        // The callback can only be replaced, not removed
+        self.ext.set_subscribe_cb(|| {});
+
+        // libpulse refuses to unsubscribe once disconnected, there is nothing to stop then
+        if self.context.is_ready() {
+            self.ext.subscribe(false, |_| {});
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use pulse::volume::Volume;
+
+    use super::*;
+    use crate::test_server::{TestServer, SINKS};
+
+    fn entry(name: &str) -> StreamRestoreEntry {
+        let mut channel_map = channelmap::Map::default();
+        channel_map.init_stereo();
+
+        let mut volume = ChannelVolumes::default();
+        volume.set(2, Volume(Volume::NORMAL.0 / 2));
+
+        StreamRestoreEntry {
+            name: name.to_string(),
+            channel_map,
+            volume,
+            device: Some(SINKS[1].to_string()),
+            mute: true,
+        }
+    }
+
+    #[test]
+    fn read_write_delete() {
+        let server = TestServer::spawn();
+
+        futures::executor::block_on(async {
+            let context = server.connect().await;
+            let mut events = context.subscribe_stream_restore();
+
+            let name = "sink-input-by-application-name:Test Player";
+            let mut restore = context.stream_restore();
+            assert!(
+                restore
+                    .write(&[entry(name)], UpdateMode::Merge, false)
+                    .await
+            );
+            events.next().await.unwrap();
+
+            let entries = restore.read().await.unwrap();
+            let stored = entries.iter().find(|e| e.name == name).unwrap();
+            assert!(stored.mute);
+            assert_eq!(stored.device.as_deref(), Some(SINKS[1]));
+            assert_eq!(stored.volume.max(), Volume(Volume::NORMAL.0 / 2));
+            assert_eq!(
+                stored.key(),
+                Some(("sink-input-by-application-name", "Test Player"))
+            );
+
+            assert!(restore.delete(&[name]).await);
+            let entries = restore.read().await.unwrap();
+            assert!(entries.iter().all(|e| e.name != name));
+        });
+    }
+}
@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+use pulse::{
+    channelmap::{self, Position},
+    error::PAErr,
+};
+
+use crate::{
+    dsp::{Generator, Signal},
+    Context,
+};
+
+const SAMPLE_RATE: u32 = 48000;
+
+/// Silence between two channels, so it's easy to tell where one ends.
+const CHANNEL_GAP: Duration = Duration::from_millis(300);
+
+/// Plays `signal` on every position of `channel_map` in turn, `duration` each.
+///
+/// `on_channel` is called with the channel index and its position right before it starts playing.
+pub async fn play_test_signal<F>(
+    context: &Context,
+    id: u32,
+    channel_map: &channelmap::Map,
+    signal: Signal,
+    duration: Duration,
+    mut on_channel: F,
+) -> Result<(), PAErr>
+where
+    F: FnMut(usize, Position),
+{
+    let mut stream = super::stream::crate_playback_stream(
+        context,
+        id,
+        SAMPLE_RATE,
+        channel_map,
+        Duration::from_millis(50),
+    )?;
+
+    let channels = stream.channels();
+    let mut generator = Generator::new(signal, SAMPLE_RATE);
+
+    let audible = (duration.as_secs_f32() * SAMPLE_RATE as f32) as usize;
+    let total = audible + (CHANNEL_GAP.as_secs_f32() * SAMPLE_RATE as f32) as usize;
+
+    for (channel, position) in channel_map.get().iter().enumerate() {
+        on_channel(channel, *position);
+        generator.reset();
+
+        let mut done = 0;
+        while done < total {
+            let frames = stream.writable().await?.min(total - done);
+
+            let mut mono = vec![0.0; frames];
+            let tone = frames.min(audible.saturating_sub(done));
+            generator.fill(&mut mono[..tone]);
+
+            let mut interleaved = vec![0.0; frames * channels];
+            for (frame, v) in mono.into_iter().enumerate() {
+                interleaved[frame * channels + channel] = v;
+            }
+
+            stream.write(&interleaved)?;
+            done += frames;
+        }
+    }
+
+    stream.drain().await;
+
+    Ok(())
+}
@@ -0,0 +1,215 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use futures::channel::mpsc::Receiver;
+use pulse::{def::TimingInfo, stream::Latency, time::MicroSeconds};
+
+use crate::Context;
+
+/// Snapshot of a stream's timing state.
+#[derive(Debug, Clone, Copy)]
+pub struct StreamStats {
+    /// Total latency, `None` until the first timing update arrived.
+    pub latency: Option<Latency>,
+    /// Current playback/record time, `None` until the first timing update arrived.
+    pub time: Option<MicroSeconds>,
+    /// Raw timing info of the last update.
+    pub timing_info: Option<TimingInfo>,
+    /// Number of buffer underflows since the stream was created.
+    pub underflows: u64,
+    /// Number of buffer overflows since the stream was created.
+    pub overflows: u64,
+}
+
+/// Underflow/overflow counters and latency update notifications of a single stream.
+pub(crate) struct Timing {
+    underflows: Arc<AtomicU64>,
+    overflows: Arc<AtomicU64>,
+    /// Holds at most one pending update, so unread updates don't pile up.
+    pub(crate) updates: Receiver<()>,
+}
+
+impl Timing {
+    pub(crate) fn attach(stream: &mut pulse::stream::Stream) -> Self {
+        let underflows = Arc::new(AtomicU64::new(0));
+        let overflows = Arc::new(AtomicU64::new(0));
+
+        stream.set_underflow_callback(Some(Box::new({
+            let underflows = underflows.clone();
+            move || {
+                underflows.fetch_add(1, Ordering::Relaxed);
+            }
+        })));
+
+        stream.set_overflow_callback(Some(Box::new({
+            let overflows = overflows.clone();
+            move || {
+                overflows.fetch_add(1, Ordering::Relaxed);
+            }
+        })));
+
+        // A single sender gets one slot, further updates are dropped until it is read
+        let (mut tx, updates) = futures::channel::mpsc::channel::<()>(0);
+        stream.set_latency_update_callback(Some(Box::new(move || {
+            tx.try_send(()).ok();
+        })));
+
+        Self {
+            underflows,
+            overflows,
+            updates,
+        }
+    }
+
+    pub(crate) fn detach(stream: &mut pulse::stream::Stream) {
+        stream.set_underflow_callback(None);
+        stream.set_overflow_callback(None);
+        stream.set_latency_update_callback(None);
+    }
+
+    pub(crate) fn underflows(&self) -> u64 {
+        self.underflows.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn overflows(&self) -> u64 {
+        self.overflows.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn stats(&self, stream: &mut pulse::stream::Stream) -> StreamStats {
+        StreamStats {
+            latency: stream.get_latency().ok(),
+            time: stream.get_time().ok().flatten(),
+            timing_info: stream.get_timing_info().copied(),
+            underflows: self.underflows(),
+            overflows: self.overflows(),
+        }
+    }
+}
+
+/// Requests a timing info update, resolves with `true` on success.
+pub(crate) async fn update_timing_info(
+    context: &Context,
+    stream: &mut pulse::stream::Stream,
+) -> bool {
+    let (tx, rx) = futures::channel::oneshot::channel::<bool>();
+
+    let mut tx = Some(tx);
+    let operation = {
+        let _lock = context.lock();
+        stream.update_timing_info(Some(Box::new(move |success| {
+            if let Some(tx) = tx.take() {
+                tx.send(success).ok();
+            }
+        })))
+    };
+
+    context
+        .wait(operation, rx, context.operation_timeout())
+        .await
+        .unwrap_or(false)
+}
+
+/// Adds timing accessors to a stream wrapper with `stream`, `timing` and `context` fields.
+macro_rules! impl_timing {
+    ($ty:ty) => {
+        impl $ty {
+            /// Asks the server for fresh timing info.
+            ///
+            /// Resolves with `true` on success.
+            pub async fn update_timing_info(&mut self) -> bool {
+                super::timing::update_timing_info(&self.context, &mut self.stream).await
+            }
+
+            /// Total stream latency, based on the last timing info update.
+            pub fn latency(&self) -> Result<pulse::stream::Latency, PAErr> {
+                let _lock = self.context.lock();
+                self.stream.get_latency()
+            }
+
+            /// Current playback/record time, based on the last timing info update.
+            pub fn time(&self) -> Result<Option<pulse::time::MicroSeconds>, PAErr> {
+                let _lock = self.context.lock();
+                self.stream.get_time()
+            }
+
+            /// Raw timing info of the last update.
+            pub fn timing_info(&mut self) -> Option<pulse::def::TimingInfo> {
+                let _lock = self.context.lock();
+                self.stream.get_timing_info().copied()
+            }
+
+            /// Number of buffer underflows since the stream was created.
+            pub fn underflows(&self) -> u64 {
+                self.timing.underflows()
+            }
+
+            /// Number of buffer overflows since the stream was created.
+            pub fn overflows(&self) -> u64 {
+                self.timing.overflows()
+            }
+
+            /// Refreshes timing info and returns a snapshot of it.
+            pub async fn stats(&mut self) -> super::timing::StreamStats {
+                self.update_timing_info().await;
+
+                let _lock = self.context.lock();
+                self.timing.stats(&mut self.stream)
+            }
+
+            /// Waits for the next timing update pushed by the server.
+            ///
+            /// Only streams created with `timing_updates` get them pushed. Updates arriving while
+            /// none is awaited are merged into one.
+            pub async fn next_timing_update(&mut self) -> Option<super::timing::StreamStats> {
+                futures::StreamExt::next(&mut self.timing.updates).await?;
+
+                let _lock = self.context.lock();
+                Some(self.timing.stats(&mut self.stream))
+            }
+        }
+    };
+}
+
+pub(crate) use impl_timing;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::StreamExt;
+
+    use crate::test_server::TestServer;
+
+    #[test]
+    fn timing_updates() {
+        let server = TestServer::spawn();
+
+        futures::executor::block_on(async {
+            let context = server.connect().await;
+            let sink = context.introspect().sink_list().await.unwrap().remove(0);
+
+            let mut peaks = context.crate_stream(sink.monitor_source, None, true);
+            peaks.next().await.unwrap();
+
+            let stats = peaks.next_timing_update().await.unwrap();
+            assert!(stats.timing_info.is_some());
+            assert!(peaks.latency().is_ok());
+
+            let mut playback = context
+                .crate_playback_stream(
+                    sink.index,
+                    48000,
+                    &sink.channel_map,
+                    Duration::from_millis(50),
+                )
+                .unwrap();
+            playback.writable().await.unwrap();
+
+            assert!(playback.next_timing_update().await.is_some());
+            assert!(playback.latency().is_ok());
+            assert_eq!(playback.stats().await.overflows, 0);
+        });
+    }
+}
@@ -0,0 +1,85 @@
+use super::fft::{fft, ifft, Complex};
+
+/// Finds where `reference` starts within `recorded` using FFT cross-correlation.
+///
+/// Returns the offset in samples and the normalized correlation at that offset (`0.0..=1.0`),
+/// or `None` if `recorded` is shorter than `reference` or silent.
+pub fn find_delay(reference: &[f32], recorded: &[f32]) -> Option<(usize, f32)> {
+    if reference.is_empty() || recorded.len() < reference.len() {
+        return None;
+    }
+
+    let n = (recorded.len() + reference.len()).next_power_of_two();
+
+    let mut a = vec![Complex::default(); n];
+    let mut b = vec![Complex::default(); n];
+
+    for (v, s) in a.iter_mut().zip(recorded) {
+        v.re = *s;
+    }
+    for (v, s) in b.iter_mut().zip(reference) {
+        v.re = *s;
+    }
+
+    fft(&mut a);
+    fft(&mut b);
+
+    // A * conj(B)
+    for (a, b) in a.iter_mut().zip(b.iter()) {
+        *a = Complex::new(a.re * b.re + a.im * b.im, a.im * b.re - a.re * b.im);
+    }
+
+    ifft(&mut a);
+
+    let (lag, peak) = a[..=recorded.len() - reference.len()]
+        .iter()
+        .map(|v| v.re)
+        .enumerate()
+        .max_by(|x, y| x.1.partial_cmp(&y.1).unwrap_or(std::cmp::Ordering::Equal))?;
+
+    let reference_energy: f32 = reference.iter().map(|v| v * v).sum();
+    let recorded_energy: f32 = recorded[lag..lag + reference.len()]
+        .iter()
+        .map(|v| v * v)
+        .sum();
+
+    let norm = (reference_energy * recorded_energy).sqrt();
+    if norm <= f32::EPSILON {
+        return None;
+    }
+
+    Some((lag, (peak / norm).clamp(0.0, 1.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::generator::chirp;
+
+    #[test]
+    fn delayed_chirp() {
+        let reference = chirp(48000, 0.05, 200.0, 8000.0);
+
+        let delay = 1234;
+        let mut recorded = vec![0.0; 9600];
+        for (i, v) in reference.iter().enumerate() {
+            recorded[delay + i] = v * 0.3;
+        }
+
+        // Some deterministic "noise" on top
+        for (i, v) in recorded.iter_mut().enumerate() {
+            *v += ((i * 7919) % 100) as f32 / 100.0 * 0.02 - 0.01;
+        }
+
+        let (lag, confidence) = find_delay(&reference, &recorded).unwrap();
+        assert_eq!(lag, delay);
+        assert!(confidence > 0.8);
+    }
+
+    #[test]
+    fn silence() {
+        let reference = chirp(48000, 0.05, 200.0, 8000.0);
+        assert!(find_delay(&reference, &vec![0.0; 4800]).is_none());
+        assert!(find_delay(&reference, &[0.0; 10]).is_none());
+    }
+}
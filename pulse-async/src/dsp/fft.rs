@@ -0,0 +1,131 @@
+use std::f32::consts::PI;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Complex {
+    pub re: f32,
+    pub im: f32,
+}
+
+impl Complex {
+    pub fn new(re: f32, im: f32) -> Self {
+        Self { re, im }
+    }
+
+    pub fn norm(&self) -> f32 {
+        (self.re * self.re + self.im * self.im).sqrt()
+    }
+}
+
+/// In-place iterative radix-2 FFT.
+///
+/// `buf.len()` has to be a power of two.
+pub fn fft(buf: &mut [Complex]) {
+    let n = buf.len();
+    assert!(n.is_power_of_two(), "FFT size has to be a power of two");
+
+    // Bit reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+
+        if i < j {
+            buf.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let half = len / 2;
+        let step = -2.0 * PI / len as f32;
+
+        for start in (0..n).step_by(len) {
+            for k in 0..half {
+                let (sin, cos) = (step * k as f32).sin_cos();
+
+                let a = buf[start + k];
+                let b = buf[start + k + half];
+
+                let t = Complex::new(b.re * cos - b.im * sin, b.re * sin + b.im * cos);
+
+                buf[start + k] = Complex::new(a.re + t.re, a.im + t.im);
+                buf[start + k + half] = Complex::new(a.re - t.re, a.im - t.im);
+            }
+        }
+
+        len <<= 1;
+    }
+}
+
+/// In-place inverse FFT, including the `1/n` scaling.
+pub fn ifft(buf: &mut [Complex]) {
+    for v in buf.iter_mut() {
+        v.im = -v.im;
+    }
+
+    fft(buf);
+
+    let scale = 1.0 / buf.len() as f32;
+    for v in buf.iter_mut() {
+        v.re *= scale;
+        v.im *= -scale;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn impulse() {
+        let mut buf = vec![Complex::default(); 8];
+        buf[0].re = 1.0;
+
+        fft(&mut buf);
+
+        for bin in buf {
+            assert!((bin.re - 1.0).abs() < 1e-6);
+            assert!(bin.im.abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn sine() {
+        let n = 64;
+        let mut buf: Vec<Complex> = (0..n)
+            .map(|i| Complex::new((2.0 * PI * 4.0 * i as f32 / n as f32).sin(), 0.0))
+            .collect();
+
+        fft(&mut buf);
+
+        let peak = buf[..n / 2]
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.norm().partial_cmp(&b.1.norm()).unwrap())
+            .unwrap()
+            .0;
+
+        assert_eq!(peak, 4);
+        assert!((buf[4].norm() - n as f32 / 2.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn round_trip() {
+        let input: Vec<Complex> = (0..32)
+            .map(|i| Complex::new(i as f32, (i * 3 % 7) as f32))
+            .collect();
+
+        let mut buf = input.clone();
+        fft(&mut buf);
+        ifft(&mut buf);
+
+        for (a, b) in input.iter().zip(buf.iter()) {
+            assert!((a.re - b.re).abs() < 1e-3);
+            assert!((a.im - b.im).abs() < 1e-3);
+        }
+    }
+}
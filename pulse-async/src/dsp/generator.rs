@@ -0,0 +1,196 @@
+use std::{f32::consts::PI, time::Duration};
+
+/// Test signal shape.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Signal {
+    /// Steady sine tone.
+    Sine { freq: f32 },
+    /// Logarithmic sine sweep from `from` to `to` Hz, restarting every `duration`.
+    Sweep {
+        from: f32,
+        to: f32,
+        duration: Duration,
+    },
+    /// Pink (1/f) noise.
+    PinkNoise,
+}
+
+/// Produces mono samples of a [`Signal`].
+pub struct Generator {
+    signal: Signal,
+    sample_rate: u32,
+    amplitude: f32,
+    phase: f32,
+    position: usize,
+    rng: u32,
+    pink: [f32; 7],
+}
+
+impl Generator {
+    pub fn new(signal: Signal, sample_rate: u32) -> Self {
+        Self {
+            signal,
+            sample_rate,
+            // -12 dBFS, loud enough to hear without hurting anyone's tweeters
+            amplitude: 0.25,
+            phase: 0.0,
+            position: 0,
+            rng: 0x1234_5678,
+            pink: [0.0; 7],
+        }
+    }
+
+    pub fn set_amplitude(&mut self, amplitude: f32) {
+        self.amplitude = amplitude.clamp(0.0, 1.0);
+    }
+
+    pub fn signal(&self) -> Signal {
+        self.signal
+    }
+
+    /// Restarts the signal from the beginning.
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+        self.position = 0;
+        self.pink = [0.0; 7];
+    }
+
+    pub fn fill(&mut self, out: &mut [f32]) {
+        for sample in out {
+            *sample = self.next_sample();
+        }
+    }
+
+    pub fn next_sample(&mut self) -> f32 {
+        let v = match self.signal {
+            Signal::Sine { freq } => self.oscillator(freq),
+            Signal::Sweep { from, to, duration } => {
+                let len = (duration.as_secs_f32() * self.sample_rate as f32).max(1.0) as usize;
+                let t = (self.position % len) as f32 / len as f32;
+
+                self.oscillator(from * (to / from).powf(t))
+            }
+            Signal::PinkNoise => self.pink_noise(),
+        };
+
+        self.position += 1;
+
+        v * self.amplitude
+    }
+
+    fn oscillator(&mut self, freq: f32) -> f32 {
+        let v = self.phase.sin();
+
+        self.phase += 2.0 * PI * freq / self.sample_rate as f32;
+        if self.phase >= 2.0 * PI {
+            self.phase -= 2.0 * PI;
+        }
+
+        v
+    }
+
+    fn white_noise(&mut self) -> f32 {
+        // xorshift32
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 17;
+        self.rng ^= self.rng << 5;
+
+        self.rng as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+
+    fn pink_noise(&mut self) -> f32 {
+        // Paul Kellet's refined pink noise filter
+        let white = self.white_noise();
+        let b = &mut self.pink;
+
+        b[0] = 0.99886 * b[0] + white * 0.0555179;
+        b[1] = 0.99332 * b[1] + white * 0.0750759;
+        b[2] = 0.969 * b[2] + white * 0.153852;
+        b[3] = 0.86650 * b[3] + white * 0.3104856;
+        b[4] = 0.55 * b[4] + white * 0.5329522;
+        b[5] = -0.7616 * b[5] - white * 0.0168980;
+
+        let pink = b[0] + b[1] + b[2] + b[3] + b[4] + b[5] + b[6] + white * 0.5362;
+        b[6] = white * 0.115926;
+
+        (pink * 0.11).clamp(-1.0, 1.0)
+    }
+}
+
+/// Generates a Hann-windowed logarithmic chirp, handy as a correlation reference.
+pub fn chirp(sample_rate: u32, duration: f32, from: f32, to: f32) -> Vec<f32> {
+    let len = (duration * sample_rate as f32) as usize;
+
+    let mut generator = Generator::new(
+        Signal::Sweep {
+            from,
+            to,
+            duration: Duration::from_secs_f32(duration),
+        },
+        sample_rate,
+    );
+    generator.set_amplitude(1.0);
+
+    (0..len)
+        .map(|i| {
+            let window = 0.5 - 0.5 * (2.0 * PI * i as f32 / len as f32).cos();
+            generator.next_sample() * window
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zero_crossings(samples: &[f32]) -> usize {
+        samples
+            .windows(2)
+            .filter(|w| (w[0] < 0.0) != (w[1] < 0.0))
+            .count()
+    }
+
+    #[test]
+    fn sine() {
+        let mut gen = Generator::new(Signal::Sine { freq: 1000.0 }, 48000);
+        let mut buf = vec![0.0; 48000];
+        gen.fill(&mut buf);
+
+        let peak = buf.iter().fold(0.0f32, |a, b| a.max(b.abs()));
+        assert!((peak - 0.25).abs() < 1e-3);
+
+        // 1000 periods, two crossings each
+        assert!((zero_crossings(&buf) as i32 - 2000).abs() <= 2);
+    }
+
+    #[test]
+    fn sweep() {
+        let mut gen = Generator::new(
+            Signal::Sweep {
+                from: 100.0,
+                to: 10000.0,
+                duration: Duration::from_secs(1),
+            },
+            48000,
+        );
+        let mut buf = vec![0.0; 48000];
+        gen.fill(&mut buf);
+
+        let start = zero_crossings(&buf[..4800]);
+        let end = zero_crossings(&buf[48000 - 4800..]);
+        assert!(end > start * 10);
+    }
+
+    #[test]
+    fn pink_noise() {
+        let mut gen = Generator::new(Signal::PinkNoise, 48000);
+        let mut buf = vec![0.0; 48000];
+        gen.fill(&mut buf);
+
+        assert!(buf.iter().all(|v| v.abs() <= 0.25));
+        assert!(buf.iter().any(|v| v.abs() > 0.01));
+
+        let mean = buf.iter().sum::<f32>() / buf.len() as f32;
+        assert!(mean.abs() < 0.05);
+    }
+}
@@ -0,0 +1,197 @@
+use std::time::Duration;
+
+use futures::StreamExt;
+
+use crate::stream::PcmStream;
+
+/// Lowest level reported by the meter, in dBFS.
+pub const METER_FLOOR_DB: f32 = -90.0;
+
+/// Converts a linear sample magnitude to dBFS, clamped to [`METER_FLOOR_DB`].
+pub fn linear_to_dbfs(v: f32) -> f32 {
+    if v <= 0.0 {
+        METER_FLOOR_DB
+    } else {
+        (20.0 * v.log10()).max(METER_FLOOR_DB)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MeterConfig {
+    /// Sample rate of the metered PCM stream.
+    pub sample_rate: u32,
+    /// How long a peak is held before it is allowed to fall.
+    pub hold_time: Duration,
+    /// Linear magnitude at (or above) which a sample counts as clipped.
+    pub clip_threshold: f32,
+}
+
+impl Default for MeterConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 48000,
+            hold_time: Duration::from_millis(1500),
+            clip_threshold: 0.999,
+        }
+    }
+}
+
+/// Single meter update, all levels in dBFS.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeterReading {
+    pub rms: f32,
+    pub peak: f32,
+    pub peak_hold: f32,
+    /// At least one sample of this chunk reached the clip threshold.
+    pub clipped: bool,
+}
+
+/// RMS/peak meter with peak-hold and clip detection.
+pub struct Meter {
+    config: MeterConfig,
+    hold: f32,
+    hold_left: usize,
+}
+
+impl Meter {
+    pub fn new(config: MeterConfig) -> Self {
+        Self {
+            config,
+            hold: 0.0,
+            hold_left: 0,
+        }
+    }
+
+    pub fn config(&self) -> &MeterConfig {
+        &self.config
+    }
+
+    /// Forgets the held peak.
+    pub fn reset(&mut self) {
+        self.hold = 0.0;
+        self.hold_left = 0;
+    }
+
+    pub fn process(&mut self, samples: &[f32]) -> MeterReading {
+        let mut sum = 0.0;
+        let mut peak = 0.0f32;
+
+        for sample in samples {
+            sum += sample * sample;
+            peak = peak.max(sample.abs());
+        }
+
+        let rms = if samples.is_empty() {
+            0.0
+        } else {
+            (sum / samples.len() as f32).sqrt()
+        };
+
+        let hold_samples =
+            (self.config.hold_time.as_secs_f32() * self.config.sample_rate as f32) as usize;
+
+        if peak >= self.hold || self.hold_left <= samples.len() {
+            self.hold = peak;
+            self.hold_left = hold_samples;
+        } else {
+            self.hold_left -= samples.len();
+        }
+
+        MeterReading {
+            rms: linear_to_dbfs(rms),
+            peak: linear_to_dbfs(peak),
+            peak_hold: linear_to_dbfs(self.hold),
+            clipped: peak >= self.config.clip_threshold,
+        }
+    }
+}
+
+/// Stream of meter readings computed from a [`PcmStream`], one per received fragment.
+pub struct MeterStream {
+    pcm: PcmStream,
+    meter: Meter,
+}
+
+impl MeterStream {
+    pub fn new(pcm: PcmStream, config: MeterConfig) -> Self {
+        Self {
+            pcm,
+            meter: Meter::new(config),
+        }
+    }
+}
+
+impl futures::Stream for MeterStream {
+    type Item = MeterReading;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        match self.pcm.poll_next_unpin(cx) {
+            std::task::Poll::Ready(Some(samples)) => {
+                std::task::Poll::Ready(Some(self.meter.process(&samples)))
+            }
+            std::task::Poll::Ready(None) => std::task::Poll::Ready(None),
+            std::task::Poll::Pending => std::task::Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn sine(amplitude: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (2.0 * PI * 1000.0 * i as f32 / 48000.0).sin())
+            .collect()
+    }
+
+    #[test]
+    fn dbfs() {
+        assert_eq!(linear_to_dbfs(1.0), 0.0);
+        assert!((linear_to_dbfs(0.5) + 6.0206).abs() < 1e-3);
+        assert_eq!(linear_to_dbfs(0.0), METER_FLOOR_DB);
+    }
+
+    #[test]
+    fn sine_levels() {
+        let mut meter = Meter::new(MeterConfig::default());
+        let reading = meter.process(&sine(0.5, 4800));
+
+        assert!((reading.peak + 6.02).abs() < 0.1);
+        assert!((reading.rms + 9.03).abs() < 0.1);
+        assert!(!reading.clipped);
+    }
+
+    #[test]
+    fn clip() {
+        let mut meter = Meter::new(MeterConfig::default());
+        let reading = meter.process(&[0.0, 1.0, -0.2]);
+
+        assert!(reading.clipped);
+        assert!(!meter.process(&[0.5]).clipped);
+    }
+
+    #[test]
+    fn peak_hold() {
+        let config = MeterConfig {
+            hold_time: Duration::from_millis(100),
+            ..Default::default()
+        };
+        let mut meter = Meter::new(config);
+
+        meter.process(&[0.5]);
+
+        // 50ms of silence, peak is still held
+        let reading = meter.process(&vec![0.0; 2400]);
+        assert!((reading.peak_hold + 6.02).abs() < 0.1);
+        assert_eq!(reading.peak, METER_FLOOR_DB);
+
+        // another 100ms, hold expired
+        let reading = meter.process(&vec![0.0; 4800]);
+        assert_eq!(reading.peak_hold, METER_FLOOR_DB);
+    }
+}
@@ -0,0 +1,10 @@
+pub mod correlation;
+pub mod fft;
+pub mod generator;
+pub mod meter;
+pub mod spectrum;
+
+pub use correlation::find_delay;
+pub use generator::{chirp, Generator, Signal};
+pub use meter::{linear_to_dbfs, Meter, MeterConfig, MeterReading, MeterStream, METER_FLOOR_DB};
+pub use spectrum::{SpectrumAnalyzer, SpectrumConfig, SpectrumStream, SPECTRUM_FLOOR_DB};
@@ -0,0 +1,249 @@
+use std::{collections::VecDeque, f32::consts::PI};
+
+use futures::StreamExt;
+
+use super::fft::{fft, Complex};
+use crate::stream::PcmStream;
+
+/// Lowest value reported for a band, in dBFS.
+pub const SPECTRUM_FLOOR_DB: f32 = -90.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpectrumConfig {
+    /// Sample rate of the analysed PCM stream.
+    pub sample_rate: u32,
+    /// Size of the FFT window, has to be a power of two.
+    pub fft_size: usize,
+    /// Number of log-spaced bands.
+    pub bands: usize,
+    /// Lower edge of the first band in Hz.
+    pub min_freq: f32,
+    /// Upper edge of the last band in Hz.
+    pub max_freq: f32,
+    /// How many frames per second should be produced.
+    pub frame_rate: u32,
+}
+
+impl Default for SpectrumConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 48000,
+            fft_size: 2048,
+            bands: 32,
+            min_freq: 20.0,
+            max_freq: 20000.0,
+            frame_rate: 30,
+        }
+    }
+}
+
+/// Turns mono PCM into log-spaced band magnitudes (in dBFS) at a fixed frame rate.
+pub struct SpectrumAnalyzer {
+    config: SpectrumConfig,
+    window: Vec<f32>,
+    window_gain: f32,
+    history: VecDeque<f32>,
+    buffer: Vec<Complex>,
+    bands: Vec<(usize, usize)>,
+    hop: usize,
+    since_frame: usize,
+}
+
+impl SpectrumAnalyzer {
+    pub fn new(config: SpectrumConfig) -> Self {
+        let n = config.fft_size;
+
+        // Hann window
+        let window: Vec<f32> = (0..n)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / n as f32).cos())
+            .collect();
+        let window_gain = window.iter().sum::<f32>() / 2.0;
+
+        let bin_width = config.sample_rate as f32 / n as f32;
+        let max_freq = config.max_freq.min(config.sample_rate as f32 / 2.0);
+        let ratio = max_freq / config.min_freq;
+
+        // Bin 0 is the DC offset. Bands narrower than a bin get one of their own, pushing the
+        // following ones up until the log spacing is wider than a bin.
+        let mut next = 1;
+        let bands = (0..config.bands)
+            .map(|i| {
+                let lo = config.min_freq * ratio.powf(i as f32 / config.bands as f32);
+                let hi = config.min_freq * ratio.powf((i + 1) as f32 / config.bands as f32);
+
+                let lo = ((lo / bin_width) as usize).max(next).min(n / 2 - 1);
+                let hi = ((hi / bin_width) as usize).clamp(lo + 1, n / 2);
+                next = hi;
+
+                (lo, hi)
+            })
+            .collect();
+
+        Self {
+            config,
+            window,
+            window_gain,
+            history: VecDeque::from(vec![0.0; n]),
+            buffer: vec![Complex::default(); n],
+            bands,
+            hop: (config.sample_rate / config.frame_rate.max(1)).max(1) as usize,
+            since_frame: 0,
+        }
+    }
+
+    pub fn config(&self) -> &SpectrumConfig {
+        &self.config
+    }
+
+    /// Feeds samples into the analyzer.
+    ///
+    /// Returns the newest frame if enough samples were collected since the last one.
+    pub fn push(&mut self, samples: &[f32]) -> Option<Vec<f32>> {
+        let mut frame = None;
+
+        for &sample in samples {
+            self.history.pop_front();
+            self.history.push_back(sample);
+
+            self.since_frame += 1;
+            if self.since_frame >= self.hop {
+                self.since_frame = 0;
+                frame = Some(());
+            }
+        }
+
+        frame.map(|_| self.analyze())
+    }
+
+    fn analyze(&mut self) -> Vec<f32> {
+        for ((bin, sample), w) in self
+            .buffer
+            .iter_mut()
+            .zip(self.history.iter())
+            .zip(self.window.iter())
+        {
+            *bin = Complex::new(sample * w, 0.0);
+        }
+
+        fft(&mut self.buffer);
+
+        self.bands
+            .iter()
+            .map(|&(lo, hi)| {
+                let peak = self.buffer[lo..hi]
+                    .iter()
+                    .map(Complex::norm)
+                    .fold(0.0, f32::max);
+
+                let amplitude = peak / self.window_gain;
+
+                (20.0 * amplitude.log10()).max(SPECTRUM_FLOOR_DB)
+            })
+            .collect()
+    }
+}
+
+/// Stream of spectrum frames computed from a [`PcmStream`].
+pub struct SpectrumStream {
+    pcm: PcmStream,
+    analyzer: SpectrumAnalyzer,
+}
+
+impl SpectrumStream {
+    pub fn new(pcm: PcmStream, config: SpectrumConfig) -> Self {
+        Self {
+            pcm,
+            analyzer: SpectrumAnalyzer::new(config),
+        }
+    }
+}
+
+impl futures::Stream for SpectrumStream {
+    type Item = Vec<f32>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        loop {
+            match self.pcm.poll_next_unpin(cx) {
+                std::task::Poll::Ready(Some(samples)) => {
+                    if let Some(frame) = self.analyzer.push(&samples) {
+                        return std::task::Poll::Ready(Some(frame));
+                    }
+                }
+                std::task::Poll::Ready(None) => return std::task::Poll::Ready(None),
+                std::task::Poll::Pending => return std::task::Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, amplitude: f32, rate: u32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (2.0 * PI * freq * i as f32 / rate as f32).sin())
+            .collect()
+    }
+
+    #[test]
+    fn frame_rate() {
+        let config = SpectrumConfig::default();
+        let mut analyzer = SpectrumAnalyzer::new(config);
+
+        let hop = (config.sample_rate / config.frame_rate) as usize;
+
+        assert!(analyzer.push(&vec![0.0; hop - 1]).is_none());
+        assert!(analyzer.push(&[0.0]).is_some());
+    }
+
+    #[test]
+    fn hum() {
+        let config = SpectrumConfig::default();
+        let mut analyzer = SpectrumAnalyzer::new(config);
+
+        let frame = analyzer
+            .push(&sine(50.0, 0.5, config.sample_rate, config.fft_size))
+            .unwrap();
+
+        assert_eq!(frame.len(), config.bands);
+
+        let loudest = frame
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+            .unwrap()
+            .0;
+
+        let (lo, hi) = analyzer.bands[loudest];
+        let bin_width = config.sample_rate as f32 / config.fft_size as f32;
+        assert!(lo as f32 * bin_width <= 50.0 + bin_width);
+        assert!(hi as f32 * bin_width >= 50.0 - bin_width);
+
+        // -6 dBFS sine, give or take the window scalloping loss
+        assert!((frame[loudest] + 6.0).abs() < 2.0);
+    }
+
+    #[test]
+    fn bands() {
+        let analyzer = SpectrumAnalyzer::new(SpectrumConfig::default());
+
+        assert!(analyzer.bands.iter().all(|&(lo, hi)| lo >= 1 && hi > lo));
+        assert!(analyzer
+            .bands
+            .windows(2)
+            .all(|pair| pair[0].0 < pair[1].0 && pair[0].1 <= pair[1].0));
+    }
+
+    #[test]
+    fn silence() {
+        let config = SpectrumConfig::default();
+        let mut analyzer = SpectrumAnalyzer::new(config);
+
+        let frame = analyzer.push(&vec![0.0; config.fft_size]).unwrap();
+        assert!(frame.iter().all(|&db| db == SPECTRUM_FLOOR_DB));
+    }
+}
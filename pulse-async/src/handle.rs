@@ -0,0 +1,430 @@
+//! `Send + Sync` access to a [`Context`] living on its own thread.
+//!
+//! [`Context`] is bound to the thread that created it. [`ContextHandle`] spawns an actor thread
+//! owning a threaded-mainloop [`Context`] and forwards every request to it over a channel, so it
+//! can be cloned into worker threads and awaited from any executor.
+
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::{
+    channel::{mpsc, oneshot},
+    executor::LocalPool,
+    future::LocalBoxFuture,
+    task::LocalSpawnExt,
+    StreamExt,
+};
+use pulse::{
+    context::{subscribe::InterestMaskSet, FlagSet},
+    error::PAErr,
+    proplist::Proplist,
+    volume::ChannelVolumes,
+};
+
+use crate::{
+    BatchOp, BatchResults, Context, Introspector, ServerInfo, SinkInfo, SinkInputInfo, SourceInfo,
+    SourceOutputInfo, StatInfo, SubscribeEvent,
+};
+
+type Command = Box<dyn FnOnce(Context) -> LocalBoxFuture<'static, ()> + Send>;
+
+type Subscriber = (InterestMaskSet, mpsc::UnboundedSender<SubscribeEvent>);
+
+/// Cloneable, thread-safe handle to a [`Context`] owned by an actor thread.
+///
+/// The actor shuts down once the last handle is dropped.
+#[derive(Clone)]
+pub struct ContextHandle {
+    tx: mpsc::UnboundedSender<Command>,
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+}
+
+impl std::fmt::Debug for ContextHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextHandle").finish()
+    }
+}
+
+impl ContextHandle {
+    /// Spawns the actor thread, the context still has to be connected with [`ContextHandle::connect`].
+    pub fn spawn(name: &str, proplist: Proplist) -> Self {
+        let (tx, mut rx) = mpsc::unbounded::<Command>();
+        let name = name.to_string();
+
+        std::thread::Builder::new()
+            .name("pulse-async".into())
+            .spawn(move || {
+                let context = Context::new_threaded_with_proplist(&name, &proplist);
+
+                let mut pool = LocalPool::new();
+                let spawner = pool.spawner();
+
+                pool.run_until(async move {
+                    while let Some(command) = rx.next().await {
+                        spawner.spawn_local(command(context.clone())).ok();
+                    }
+                });
+            })
+            .expect("Failed to spawn context thread");
+
+        Self {
+            tx,
+            subscribers: Default::default(),
+        }
+    }
+
+    /// Runs `f` on the actor thread and resolves with its output.
+    ///
+    /// Fails only if the actor thread is gone.
+    pub async fn run<F, Fut, T>(&self, f: F) -> Result<T, ()>
+    where
+        F: FnOnce(Context) -> Fut + Send + 'static,
+        Fut: Future<Output = T> + 'static,
+        T: Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+
+        let command: Command = Box::new(move |context| {
+            Box::pin(async move {
+                tx.send(f(context).await).ok();
+            })
+        });
+
+        self.tx.unbounded_send(command).map_err(|_| ())?;
+
+        rx.await.map_err(|_| ())
+    }
+
+    pub async fn connect(&self, server: Option<&str>, flags: FlagSet) -> Result<(), PAErr> {
+        let server = server.map(str::to_string);
+
+        self.run(move |context| async move { context.connect(server.as_deref(), flags).await })
+            .await
+            .unwrap_or_else(|_| Err(pulse::error::Code::ConnectionTerminated.into()))
+    }
+
+    pub fn introspect(&self) -> HandleIntrospector {
+        HandleIntrospector {
+            handle: self.clone(),
+            timeout: None,
+        }
+    }
+
+    /// Sends operations to the server at once, see [`Context::batch`].
+    ///
+    /// Mutes, port and default device changes and moving streams go through here.
+    pub async fn batch(&self, ops: Vec<BatchOp>) -> Result<BatchResults, ()> {
+        self.run(move |context| async move {
+            let mut batch = context.batch();
+            for op in ops {
+                batch.push(op);
+            }
+
+            batch.run().await
+        })
+        .await
+    }
+
+    /// Subscribes to server events matching `mask`.
+    ///
+    /// The context has a single subscription, shared by every subscriber of every clone of this
+    /// handle.
+    pub fn subscribe(
+        &self,
+        mask: InterestMaskSet,
+    ) -> impl futures::Stream<Item = SubscribeEvent> + Send {
+        let (tx, rx) = mpsc::unbounded::<SubscribeEvent>();
+        self.subscribers.lock().unwrap().push((mask, tx));
+
+        let subscribers = self.subscribers.clone();
+        let command: Command = Box::new(move |context| {
+            Box::pin(async move {
+                let mask = {
+                    let mut subscribers = subscribers.lock().unwrap();
+                    subscribers.retain(|(_, tx)| !tx.is_closed());
+                    subscribers
+                        .iter()
+                        .fold(InterestMaskSet::NULL, |all, (mask, _)| all | *mask)
+                };
+
+                // Replaces the subscription of earlier subscribers, whose loop ends with it
+                let mut events = context.subscribe(mask);
+
+                while let Some(event) = events.next().await {
+                    let mut subscribers = subscribers.lock().unwrap();
+
+                    subscribers.retain(|(mask, tx)| {
+                        let interested = match event {
+                            Ok((Some(facility), _, _)) => {
+                                mask.contains(facility.to_interest_mask())
+                            }
+                            _ => true,
+                        };
+
+                        if !interested {
+                            return !tx.is_closed();
+                        }
+
+                        tx.unbounded_send(event).is_ok()
+                    });
+                }
+            })
+        });
+
+        self.tx.unbounded_send(command).ok();
+
+        rx
+    }
+}
+
+/// Thread-safe counterpart of [`Introspector`].
+#[derive(Debug, Clone)]
+pub struct HandleIntrospector {
+    handle: ContextHandle,
+    /// `None` keeps the context's default.
+    timeout: Option<Option<Duration>>,
+}
+
+/// Forwards an [`Introspector`] call to the actor thread, `$fail` is returned if it is gone.
+macro_rules! forward {
+    ($(#[$attr:meta])* fn $name:ident($($arg:ident: $ty:ty),*) -> $ret:ty = $fail:expr, |$i:ident| $call:expr;) => {
+        $(#[$attr])*
+        pub async fn $name(&self, $($arg: $ty),*) -> $ret {
+            let timeout = self.timeout;
+
+            self.handle
+                .run(move |context| async move {
+                    let $i = &mut introspector(&context, timeout);
+                    $call.await
+                })
+                .await
+                .unwrap_or($fail)
+        }
+    };
+}
+
+fn introspector(context: &Context, timeout: Option<Option<Duration>>) -> Introspector<'_> {
+    match timeout {
+        Some(timeout) => context.introspect().with_timeout(timeout),
+        None => context.introspect(),
+    }
+}
+
+impl HandleIntrospector {
+    /// See [`Introspector::with_timeout`].
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    forward! {
+        /// Gets the card list.
+        fn card_list() -> Result<Vec<String>, ()> = Err(()), |i| i.card_list();
+    }
+
+    forward! {
+        /// Gets the client list.
+        fn client_list() -> Result<Vec<String>, ()> = Err(()), |i| i.client_list();
+    }
+
+    forward! {
+        /// Gets information about the server.
+        fn server_info() -> Result<ServerInfo, ()> = Err(()), |i| i.server_info();
+    }
+
+    forward! {
+        /// Gets memory usage statistics of the server.
+        fn stat() -> Result<StatInfo, ()> = Err(()), |i| i.stat();
+    }
+
+    forward! {
+        /// Gets the sink list.
+        fn sink_list() -> Result<Vec<SinkInfo>, ()> = Err(()), |i| i.sink_list();
+    }
+
+    forward! {
+        /// Gets information about a sink by its index.
+        fn sink(id: u32) -> Result<SinkInfo, ()> = Err(()), |i| i.sink(id);
+    }
+
+    forward! {
+        /// Sets the volume of a sink in percent, resolves with `true` on success.
+        fn set_sink_volume(id: u32, v: f64) -> bool = false, |i| i.set_sink_volume(id, v);
+    }
+
+    forward! {
+        /// Sets the volume of every channel of a sink.
+        fn set_sink_volumes(id: u32, volume: ChannelVolumes) -> bool = false,
+            |i| i.set_sink_volumes(id, &volume);
+    }
+
+    forward! {
+        /// Gets the source list.
+        fn source_list() -> Result<Vec<SourceInfo>, ()> = Err(()), |i| i.source_list();
+    }
+
+    forward! {
+        /// Gets information about a source by its index.
+        fn source(id: u32) -> Result<SourceInfo, ()> = Err(()), |i| i.source(id);
+    }
+
+    forward! {
+        /// Sets the volume of a source in percent, resolves with `true` on success.
+        fn set_source_volume(id: u32, v: f64) -> bool = false, |i| i.set_source_volume(id, v);
+    }
+
+    forward! {
+        /// Sets the volume of every channel of a source.
+        fn set_source_volumes(id: u32, volume: ChannelVolumes) -> bool = false,
+            |i| i.set_source_volumes(id, &volume);
+    }
+
+    forward! {
+        /// Mutes or unmutes a source.
+        fn set_source_mute(id: u32, mute: bool) -> bool = false, |i| i.set_source_mute(id, mute);
+    }
+
+    /// Switches a source to the port named `port`.
+    pub async fn set_source_port(&self, id: u32, port: &str) -> bool {
+        let timeout = self.timeout;
+        let port = port.to_string();
+
+        self.handle
+            .run(move |context| async move {
+                introspector(&context, timeout)
+                    .set_source_port(id, &port)
+                    .await
+            })
+            .await
+            .unwrap_or(false)
+    }
+
+    forward! {
+        /// Gets the sink input list.
+        fn sink_input_list() -> Result<Vec<SinkInputInfo>, ()> = Err(()),
+            |i| i.sink_input_list();
+    }
+
+    forward! {
+        /// Gets some information about a sink input by its index.
+        fn sink_input(id: u32) -> Result<SinkInputInfo, ()> = Err(()), |i| i.sink_input(id);
+    }
+
+    forward! {
+        /// Sets the volume of a sink input stream, resolves with `true` on success.
+        fn set_sink_input_volume(id: u32, v: f64) -> bool = false,
+            |i| i.set_sink_input_volume(id, v);
+    }
+
+    forward! {
+        /// Sets the volume of every channel of a sink input stream.
+        fn set_sink_input_volumes(id: u32, volume: ChannelVolumes) -> bool = false,
+            |i| i.set_sink_input_volumes(id, &volume);
+    }
+
+    forward! {
+        /// Gets the source output list.
+        fn source_output_list() -> Result<Vec<SourceOutputInfo>, ()> = Err(()),
+            |i| i.source_output_list();
+    }
+
+    forward! {
+        /// Gets some information about a source output by its index.
+        fn source_output(id: u32) -> Result<SourceOutputInfo, ()> = Err(()),
+            |i| i.source_output(id);
+    }
+
+    forward! {
+        /// Sets the volume of a source output stream, resolves with `true` on success.
+        fn set_source_output_volume(id: u32, v: f64) -> bool = false,
+            |i| i.set_source_output_volume(id, v);
+    }
+
+    forward! {
+        /// Sets the volume of every channel of a source output stream.
+        fn set_source_output_volumes(id: u32, volume: ChannelVolumes) -> bool = false,
+            |i| i.set_source_output_volumes(id, &volume);
+    }
+
+    forward! {
+        /// Mutes or unmutes a source output stream.
+        fn set_source_output_mute(id: u32, mute: bool) -> bool = false,
+            |i| i.set_source_output_mute(id, mute);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pulse::context::subscribe::Facility;
+
+    use super::*;
+    use crate::test_server::{TestServer, SINKS};
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn send_sync() {
+        assert_send_sync::<ContextHandle>();
+        assert_send_sync::<HandleIntrospector>();
+    }
+
+    #[test]
+    fn from_other_thread() {
+        let server = TestServer::spawn();
+
+        let props = Proplist::new().unwrap();
+        let handle = ContextHandle::spawn("Test", props);
+
+        std::thread::spawn(move || {
+            futures::executor::block_on(async move {
+                handle
+                    .connect(Some(&server.server), FlagSet::NOAUTOSPAWN)
+                    .await
+                    .unwrap();
+
+                let sinks = handle.introspect().sink_list().await.unwrap();
+                assert_eq!(sinks.len(), SINKS.len());
+            });
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn shared_subscription() {
+        let server = TestServer::spawn();
+
+        let props = Proplist::new().unwrap();
+        let handle = ContextHandle::spawn("Test", props);
+
+        futures::executor::block_on(async move {
+            handle
+                .connect(Some(&server.server), FlagSet::NOAUTOSPAWN)
+                .await
+                .unwrap();
+
+            let mut sinks = handle.subscribe(InterestMaskSet::SINK);
+            let mut sources = handle.subscribe(InterestMaskSet::SOURCE);
+
+            let introspect = handle.introspect();
+            let sink = introspect.sink_list().await.unwrap().remove(0);
+
+            // The second subscription must not have replaced the first one
+            assert!(introspect.set_sink_volume(sink.index, 50.0).await);
+            assert!(introspect.set_source_mute(sink.monitor_source, true).await);
+
+            let (facility, _, index) = sinks.next().await.unwrap().unwrap();
+            assert_eq!(facility, Some(Facility::Sink));
+            assert_eq!(index, sink.index);
+
+            let (facility, _, index) = sources.next().await.unwrap().unwrap();
+            assert_eq!(facility, Some(Facility::Source));
+            assert_eq!(index, sink.monitor_source);
+
+            assert!(introspect.source(sink.monitor_source).await.unwrap().mute);
+        });
+    }
+}
@@ -1,6 +1,20 @@
+pub mod backend;
 pub mod context;
+pub mod dsp;
+pub mod handle;
+pub mod mainloop;
+pub mod proplist;
+#[cfg(feature = "serde")]
+mod serde_impl;
+pub mod volume;
 
 pub use context::*;
+pub use handle::{ContextHandle, HandleIntrospector};
+pub use proplist::{AppMetadata, ProplistBuilder};
+pub use volume::{ChannelVolumesExt, VolumeController, VolumeLevel, VolumeTarget};
+
+#[cfg(test)]
+mod test_server;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,134 @@
+//! Mainloop backends driving a [`Context`](crate::Context).
+//!
+//! Every call into libpulse is wrapped in [`MainloopBackend::lock`]/[`MainloopBackend::unlock`],
+//! so backends running the loop on another thread can serialize access to it.
+
+use std::{any::Any, time::Duration};
+
+use pulse::{
+    mainloop::threaded,
+    proplist::Proplist,
+    time::{MicroSeconds, MonotonicTs},
+};
+
+pub trait MainloopBackend {
+    /// Creates a new libpulse context attached to this mainloop.
+    fn new_context(&mut self, name: &str, proplist: &Proplist) -> Option<pulse::context::Context>;
+
+    /// Called before any libpulse object belonging to this mainloop is touched.
+    fn lock(&mut self) {}
+
+    /// Counterpart of [`MainloopBackend::lock`].
+    fn unlock(&mut self) {}
+
+    /// Called once the context is disconnected, right before it is dropped.
+    fn shutdown(&mut self) {}
+
+    /// Calls `cb` on the loop once `after` has elapsed, called with the lock held.
+    ///
+    /// Dropping the returned handle (also with the lock held) cancels the timer. Backends without
+    /// timer support return `None`, callers then fall back to a sleeping thread.
+    fn timer(
+        &mut self,
+        _context: &pulse::context::Context,
+        _after: Duration,
+        _cb: Box<dyn FnMut()>,
+    ) -> Option<Box<dyn Any>> {
+        None
+    }
+}
+
+fn deadline(after: Duration) -> MonotonicTs {
+    MonotonicTs::now() + MicroSeconds(after.as_micros() as u64)
+}
+
+/// Runs on the thread default GLib main context, callbacks are dispatched by GLib.
+#[cfg(feature = "glib")]
+pub struct GlibMainloop {
+    mainloop: libpulse_glib_binding::Mainloop,
+}
+
+#[cfg(feature = "glib")]
+impl GlibMainloop {
+    pub fn new() -> Option<Self> {
+        Some(Self {
+            mainloop: libpulse_glib_binding::Mainloop::new(None)?,
+        })
+    }
+}
+
+#[cfg(feature = "glib")]
+impl MainloopBackend for GlibMainloop {
+    fn new_context(&mut self, name: &str, proplist: &Proplist) -> Option<pulse::context::Context> {
+        pulse::context::Context::new_with_proplist(&self.mainloop, name, proplist)
+    }
+
+    fn timer(
+        &mut self,
+        context: &pulse::context::Context,
+        after: Duration,
+        mut cb: Box<dyn FnMut()>,
+    ) -> Option<Box<dyn Any>> {
+        let event = context.rttime_new::<libpulse_glib_binding::Mainloop, _>(
+            &self.mainloop,
+            deadline(after),
+            move |_| cb(),
+        )?;
+
+        Some(Box::new(event))
+    }
+}
+
+/// Runs libpulse's own event loop on a dedicated thread.
+///
+/// Callbacks only ever complete channels, so futures returned by the crate can be awaited from
+/// any executor (tokio, async-std, `futures::executor`), no GLib required.
+pub struct ThreadedMainloop {
+    mainloop: threaded::Mainloop,
+}
+
+impl ThreadedMainloop {
+    pub fn new() -> Option<Self> {
+        let mut mainloop = threaded::Mainloop::new()?;
+        mainloop.start().ok()?;
+
+        Some(Self { mainloop })
+    }
+}
+
+impl MainloopBackend for ThreadedMainloop {
+    fn new_context(&mut self, name: &str, proplist: &Proplist) -> Option<pulse::context::Context> {
+        self.mainloop.lock();
+        let context = pulse::context::Context::new_with_proplist(&self.mainloop, name, proplist);
+        self.mainloop.unlock();
+
+        context
+    }
+
+    fn lock(&mut self) {
+        self.mainloop.lock();
+    }
+
+    fn unlock(&mut self) {
+        self.mainloop.unlock();
+    }
+
+    fn shutdown(&mut self) {
+        self.mainloop.stop();
+    }
+
+    fn timer(
+        &mut self,
+        context: &pulse::context::Context,
+        after: Duration,
+        mut cb: Box<dyn FnMut()>,
+    ) -> Option<Box<dyn Any>> {
+        let event = context.rttime_new::<threaded::Mainloop, _>(
+            &self.mainloop,
+            deadline(after),
+            move |_| cb(),
+        )?;
+
+        Some(Box::new(event))
+    }
+}
@@ -0,0 +1,184 @@
+//! Typed access to the well known properties of a [`Proplist`].
+
+use pulse::{
+    error::{Code, PAErr},
+    proplist::{properties, Proplist},
+};
+
+/// Set by PipeWire for clients running inside a Flatpak sandbox.
+pub const FLATPAK_APP_ID: &str = "pipewire.access.portal.app_id";
+
+/// Application related properties of a stream or client.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AppMetadata {
+    /// Human readable application name, `application.name`.
+    pub name: Option<String>,
+    /// Reverse domain name of the application, `application.id`.
+    pub id: Option<String>,
+    /// Icon name from the icon theme, `application.icon_name`.
+    pub icon_name: Option<String>,
+    /// Name of the executable, `application.process.binary`.
+    pub process_binary: Option<String>,
+    /// `application.process.id`.
+    pub process_id: Option<u32>,
+    /// User running the process, `application.process.user`.
+    pub process_user: Option<String>,
+    /// What the stream is for, e.g. `music`, `video` or `event`, `media.role`.
+    pub media_role: Option<String>,
+    /// `media.title`.
+    pub media_title: Option<String>,
+    /// `media.artist`.
+    pub media_artist: Option<String>,
+    /// X11 window the stream belongs to, `window.x11.xid`.
+    pub window_id: Option<u64>,
+    /// Flatpak application id, see [`FLATPAK_APP_ID`].
+    pub flatpak_id: Option<String>,
+}
+
+impl AppMetadata {
+    pub fn from_proplist(proplist: &Proplist) -> Self {
+        let get = |key: &str| proplist.get_str(key).filter(|v| !v.is_empty());
+
+        Self {
+            name: get(properties::APPLICATION_NAME),
+            id: get(properties::APPLICATION_ID),
+            icon_name: get(properties::APPLICATION_ICON_NAME),
+            process_binary: get(properties::APPLICATION_PROCESS_BINARY),
+            process_id: get(properties::APPLICATION_PROCESS_ID).and_then(|v| v.parse().ok()),
+            process_user: get(properties::APPLICATION_PROCESS_USER),
+            media_role: get(properties::MEDIA_ROLE),
+            media_title: get(properties::MEDIA_TITLE),
+            media_artist: get(properties::MEDIA_ARTIST),
+            window_id: get(properties::WINDOW_X11_XID).and_then(|v| v.parse().ok()),
+            flatpak_id: get(FLATPAK_APP_ID),
+        }
+    }
+
+    /// Icon names worth looking up in the icon theme, most specific first.
+    pub fn icon_candidates(&self) -> Vec<String> {
+        [
+            self.icon_name.clone(),
+            self.flatpak_id.clone(),
+            self.id.clone(),
+            self.name.as_deref().map(str::to_lowercase),
+            self.process_binary.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+impl From<&Proplist> for AppMetadata {
+    fn from(proplist: &Proplist) -> Self {
+        Self::from_proplist(proplist)
+    }
+}
+
+/// Builds a [`Proplist`] without a fallible call per property.
+///
+/// ```
+/// use pulse_async::proplist::ProplistBuilder;
+///
+/// let proplist = ProplistBuilder::new()
+///     .application_name("Cathode")
+///     .application_id("com.github.polymeilex.Cathode")
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ProplistBuilder {
+    properties: Vec<(String, String)>,
+}
+
+impl ProplistBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets an arbitrary property, later values override earlier ones.
+    pub fn set(mut self, key: &str, value: &str) -> Self {
+        self.properties.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn application_name(self, name: &str) -> Self {
+        self.set(properties::APPLICATION_NAME, name)
+    }
+
+    pub fn application_id(self, id: &str) -> Self {
+        self.set(properties::APPLICATION_ID, id)
+    }
+
+    pub fn icon_name(self, icon: &str) -> Self {
+        self.set(properties::APPLICATION_ICON_NAME, icon)
+    }
+
+    pub fn media_role(self, role: &str) -> Self {
+        self.set(properties::MEDIA_ROLE, role)
+    }
+
+    pub fn media_name(self, name: &str) -> Self {
+        self.set(properties::MEDIA_NAME, name)
+    }
+
+    /// Fails with [`Code::Invalid`] if a key is not a valid property name.
+    pub fn build(self) -> Result<Proplist, PAErr> {
+        let mut proplist = Proplist::new().ok_or(Code::Internal)?;
+
+        for (key, value) in self.properties {
+            proplist
+                .set_str(&key, &value)
+                .map_err(|_| PAErr::from(Code::Invalid))?;
+        }
+
+        Ok(proplist)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metadata() {
+        let proplist = ProplistBuilder::new()
+            .application_name("Firefox")
+            .icon_name("")
+            .media_role("video")
+            .set(properties::APPLICATION_PROCESS_ID, "4242")
+            .set(properties::WINDOW_X11_XID, "not a number")
+            .set(FLATPAK_APP_ID, "org.mozilla.firefox")
+            .build()
+            .unwrap();
+
+        let app = AppMetadata::from(&proplist);
+
+        assert_eq!(app.name.as_deref(), Some("Firefox"));
+        assert_eq!(app.icon_name, None);
+        assert_eq!(app.process_id, Some(4242));
+        assert_eq!(app.window_id, None);
+        assert_eq!(app.media_role.as_deref(), Some("video"));
+        assert_eq!(
+            app.icon_candidates(),
+            vec!["org.mozilla.firefox".to_string(), "firefox".to_string()]
+        );
+    }
+
+    #[test]
+    fn builder_overrides() {
+        let proplist = ProplistBuilder::new()
+            .application_id("a")
+            .application_id("b")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            proplist.get_str(properties::APPLICATION_ID).as_deref(),
+            Some("b")
+        );
+
+        assert!(ProplistBuilder::new().set("", "empty key").build().is_err());
+    }
+}
@@ -0,0 +1,347 @@
+//! `serde(with = ...)` helpers for libpulse types that don't implement serde themselves.
+//!
+//! Representations favour readability in JSON dumps: sample formats, channel positions and states
+//! are strings, volumes are raw [`Volume`] values and property lists are plain string maps.
+
+use std::collections::BTreeMap;
+
+use pulse::{
+    channelmap,
+    def::{self, SinkFlagSet, SinkState, SourceFlagSet, SourceState},
+    format,
+    proplist::Proplist,
+    sample,
+    time::MicroSeconds,
+    volume::{ChannelVolumes, Volume},
+};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+pub mod sample_spec {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Repr {
+        format: String,
+        rate: u32,
+        channels: u8,
+    }
+
+    pub fn serialize<S: Serializer>(spec: &sample::Spec, s: S) -> Result<S::Ok, S::Error> {
+        Repr {
+            format: spec
+                .format
+                .to_string()
+                .map(|s| s.to_string())
+                .unwrap_or_default(),
+            rate: spec.rate,
+            channels: spec.channels,
+        }
+        .serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<sample::Spec, D::Error> {
+        let repr = Repr::deserialize(d)?;
+
+        Ok(sample::Spec {
+            format: sample::Format::parse(&repr.format),
+            rate: repr.rate,
+            channels: repr.channels,
+        })
+    }
+}
+
+pub mod channel_map {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(map: &channelmap::Map, s: S) -> Result<S::Ok, S::Error> {
+        let positions: Vec<String> = map
+            .get()
+            .iter()
+            .map(|position| {
+                channelmap::Position::to_string(*position)
+                    .map(|s| s.to_string())
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        positions.serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<channelmap::Map, D::Error> {
+        let positions = Vec::<String>::deserialize(d)?;
+
+        if positions.len() > sample::Spec::CHANNELS_MAX as usize {
+            return Err(D::Error::custom("too many channels"));
+        }
+
+        let mut map = channelmap::Map::default();
+        map.set_len(positions.len() as u8);
+
+        for (slot, name) in map.get_mut().iter_mut().zip(&positions) {
+            *slot = channelmap::Position::from_string(name);
+        }
+
+        Ok(map)
+    }
+}
+
+pub mod channel_volumes {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(volumes: &ChannelVolumes, s: S) -> Result<S::Ok, S::Error> {
+        let volumes: Vec<u32> = volumes.get().iter().map(|v| v.0).collect();
+        volumes.serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<ChannelVolumes, D::Error> {
+        let raw = Vec::<u32>::deserialize(d)?;
+
+        if raw.len() > sample::Spec::CHANNELS_MAX as usize {
+            return Err(D::Error::custom("too many channels"));
+        }
+
+        let mut volumes = ChannelVolumes::default();
+        volumes.set_len(raw.len() as u8);
+
+        for (slot, v) in volumes.get_mut().iter_mut().zip(raw) {
+            *slot = Volume(v);
+        }
+
+        Ok(volumes)
+    }
+}
+
+pub mod volume {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(volume: &Volume, s: S) -> Result<S::Ok, S::Error> {
+        volume.0.serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Volume, D::Error> {
+        u32::deserialize(d).map(Volume)
+    }
+}
+
+pub mod micro_seconds {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(usec: &MicroSeconds, s: S) -> Result<S::Ok, S::Error> {
+        usec.0.serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<MicroSeconds, D::Error> {
+        u64::deserialize(d).map(MicroSeconds)
+    }
+}
+
+pub mod proplist {
+    use super::*;
+
+    pub(crate) fn to_map(proplist: &Proplist) -> BTreeMap<String, String> {
+        proplist
+            .iter()
+            .filter_map(|key| {
+                let value = proplist.get_str(&key)?;
+                Some((key, value))
+            })
+            .collect()
+    }
+
+    pub(crate) fn from_map(map: BTreeMap<String, String>) -> Result<Proplist, String> {
+        let mut proplist = Proplist::new().ok_or("failed to create proplist")?;
+
+        for (key, value) in map {
+            proplist
+                .set_str(&key, &value)
+                .map_err(|_| format!("invalid property {}", key))?;
+        }
+
+        Ok(proplist)
+    }
+
+    pub fn serialize<S: Serializer>(proplist: &Proplist, s: S) -> Result<S::Ok, S::Error> {
+        to_map(proplist).serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Proplist, D::Error> {
+        from_map(BTreeMap::deserialize(d)?).map_err(D::Error::custom)
+    }
+}
+
+/// A [`format::Info`] as its libpulse string form, e.g. `pcm, format.rate = "48000"`.
+pub mod format_info {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(info: &format::Info, s: S) -> Result<S::Ok, S::Error> {
+        info.print().serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<format::Info, D::Error> {
+        let s = String::deserialize(d)?;
+        format::Info::new_from_string(&s).ok_or_else(|| D::Error::custom("invalid format info"))
+    }
+}
+
+pub mod format_info_list {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(list: &[format::Info], s: S) -> Result<S::Ok, S::Error> {
+        let list: Vec<String> = list.iter().map(format::Info::print).collect();
+        list.serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<format::Info>, D::Error> {
+        Vec::<String>::deserialize(d)?
+            .iter()
+            .map(|s| {
+                format::Info::new_from_string(s)
+                    .ok_or_else(|| D::Error::custom("invalid format info"))
+            })
+            .collect()
+    }
+}
+
+pub mod port_available {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        available: &def::PortAvailable,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        match available {
+            def::PortAvailable::Unknown => "unknown",
+            def::PortAvailable::No => "no",
+            def::PortAvailable::Yes => "yes",
+        }
+        .serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<def::PortAvailable, D::Error> {
+        match String::deserialize(d)?.as_str() {
+            "unknown" => Ok(def::PortAvailable::Unknown),
+            "no" => Ok(def::PortAvailable::No),
+            "yes" => Ok(def::PortAvailable::Yes),
+            other => Err(D::Error::unknown_variant(other, &["unknown", "no", "yes"])),
+        }
+    }
+}
+
+pub mod device_type {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(device: &def::Device, s: S) -> Result<S::Ok, S::Error> {
+        match device {
+            def::Device::Sink => "sink",
+            def::Device::Source => "source",
+        }
+        .serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<def::Device, D::Error> {
+        match String::deserialize(d)?.as_str() {
+            "sink" => Ok(def::Device::Sink),
+            "source" => Ok(def::Device::Source),
+            other => Err(D::Error::unknown_variant(other, &["sink", "source"])),
+        }
+    }
+}
+
+/// Sink and source states share their variant names.
+macro_rules! device_state {
+    ($name:ident, $state:ident) => {
+        pub mod $name {
+            use super::*;
+
+            const VARIANTS: &[&str] = &["invalid", "running", "idle", "suspended"];
+
+            pub fn serialize<S: Serializer>(state: &$state, s: S) -> Result<S::Ok, S::Error> {
+                match state {
+                    $state::Invalid => "invalid",
+                    $state::Running => "running",
+                    $state::Idle => "idle",
+                    $state::Suspended => "suspended",
+                }
+                .serialize(s)
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<$state, D::Error> {
+                match String::deserialize(d)?.as_str() {
+                    "invalid" => Ok($state::Invalid),
+                    "running" => Ok($state::Running),
+                    "idle" => Ok($state::Idle),
+                    "suspended" => Ok($state::Suspended),
+                    other => Err(D::Error::unknown_variant(other, VARIANTS)),
+                }
+            }
+        }
+    };
+}
+
+device_state!(sink_state, SinkState);
+device_state!(source_state, SourceState);
+
+/// Flag sets as their raw bits.
+macro_rules! flag_set {
+    ($name:ident, $flags:ident) => {
+        pub mod $name {
+            use super::*;
+
+            pub fn serialize<S: Serializer>(flags: &$flags, s: S) -> Result<S::Ok, S::Error> {
+                flags.bits().serialize(s)
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<$flags, D::Error> {
+                u32::deserialize(d).map($flags::from_bits_truncate)
+            }
+        }
+    };
+}
+
+flag_set!(sink_flags, SinkFlagSet);
+flag_set!(source_flags, SourceFlagSet);
+
+#[cfg(test)]
+mod tests {
+    use pulse::proplist::properties;
+
+    use crate::backend::fake;
+    use crate::SinkInfo;
+
+    #[test]
+    fn sink_round_trip() {
+        let mut sink = fake::sink(3, "speakers");
+        sink.proplist
+            .set_str(properties::DEVICE_DESCRIPTION, "Speakers")
+            .unwrap();
+
+        let json = serde_json::to_value(&sink).unwrap();
+        assert_eq!(
+            json["channel_map"],
+            serde_json::json!(["front-left", "front-right"])
+        );
+        assert_eq!(json["state"], "idle");
+        assert_eq!(json["proplist"][properties::DEVICE_DESCRIPTION], "Speakers");
+
+        let back: SinkInfo = serde_json::from_value(json).unwrap();
+        assert_eq!(back.index, 3);
+        assert_eq!(back.volume, sink.volume);
+        assert_eq!(back.channel_map.get(), sink.channel_map.get());
+        assert_eq!(back.sample_spec, sink.sample_spec);
+        assert_eq!(
+            back.proplist
+                .get_str(properties::DEVICE_DESCRIPTION)
+                .as_deref(),
+            Some("Speakers")
+        );
+    }
+
+    #[test]
+    fn sink_input() {
+        let input = fake::sink_input(1, 3, "Player");
+        let json = serde_json::to_value(&input).unwrap();
+
+        assert_eq!(json["sink"], 3);
+        assert_eq!(json["proplist"][properties::APPLICATION_NAME], "Player");
+    }
+}
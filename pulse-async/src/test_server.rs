@@ -0,0 +1,133 @@
+//! Throwaway PulseAudio server for tests.
+//!
+//! Every [`TestServer`] runs its own `pulseaudio` daemon (override with `PULSE_ASYNC_TEST_DAEMON`,
+//! e.g. to point at a wrapper script) on a private socket, with state and runtime dirs in a
+//! temporary directory, so tests never touch the user's session and can run in parallel.
+
+use std::{
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use pulse::{context::FlagSet, proplist::Proplist};
+
+use crate::Context;
+
+/// Names of the null sinks every server is started with.
+pub const SINKS: [&str; 2] = ["test_sink_a", "test_sink_b"];
+
+/// Extension modules loaded besides the protocol and the sinks.
+const MODULES: &[&str] = &[
+    "module-stream-restore",
+    "module-device-restore",
+    "module-device-manager",
+];
+
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+pub struct TestServer {
+    child: Child,
+    dir: PathBuf,
+    /// Server string to pass to [`Context::connect`].
+    pub server: String,
+}
+
+impl TestServer {
+    pub fn spawn() -> Self {
+        let dir = std::env::temp_dir().join(format!(
+            "pulse-async-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir).expect("Failed to create server dir");
+
+        let socket = dir.join("native");
+        let daemon =
+            std::env::var("PULSE_ASYNC_TEST_DAEMON").unwrap_or_else(|_| "pulseaudio".into());
+
+        let mut command = Command::new(daemon);
+        command
+            .env("HOME", &dir)
+            .env("PULSE_RUNTIME_PATH", &dir)
+            .env("PULSE_STATE_PATH", &dir)
+            .env_remove("PULSE_SERVER")
+            .args([
+                "-n",
+                "--daemonize=no",
+                "--exit-idle-time=-1",
+                "--use-pid-file=no",
+                "--disable-shm=yes",
+                "--log-target=stderr",
+                "--log-level=error",
+            ])
+            .arg("-L")
+            .arg(format!(
+                "module-native-protocol-unix socket={} auth-anonymous=1",
+                socket.display()
+            ))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null());
+
+        for module in MODULES {
+            command.arg("-L").arg(module);
+        }
+
+        for sink in SINKS {
+            command.arg("-L").arg(format!(
+                "module-null-sink sink_name={} rate=48000 channels=2",
+                sink
+            ));
+        }
+
+        let child = command
+            .spawn()
+            .expect("Failed to start pulseaudio, is it installed?");
+
+        let mut server = Self {
+            child,
+            dir,
+            server: format!("unix:{}", socket.display()),
+        };
+
+        let start = Instant::now();
+        while !socket.exists() {
+            if let Ok(Some(status)) = server.child.try_wait() {
+                panic!("pulseaudio exited during startup: {}", status);
+            }
+
+            assert!(
+                start.elapsed() < STARTUP_TIMEOUT,
+                "pulseaudio did not create its socket in time"
+            );
+
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        server
+    }
+
+    /// Creates a threaded context and connects it to this server.
+    pub async fn connect(&self) -> Context {
+        let props = Proplist::new().unwrap();
+        let context = Context::new_threaded_with_proplist("Test", &props);
+
+        context
+            .connect(Some(&self.server), FlagSet::NOAUTOSPAWN)
+            .await
+            .expect("Failed to connect to test server");
+
+        context
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.child.kill().ok();
+        self.child.wait().ok();
+        std::fs::remove_dir_all(&self.dir).ok();
+    }
+}
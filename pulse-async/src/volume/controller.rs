@@ -0,0 +1,309 @@
+use std::{cell::RefCell, future::Future, rc::Rc};
+
+use pulse::volume::ChannelVolumes;
+
+use super::{ChannelVolumesExt, VolumeLevel};
+use crate::{backend::Backend, Context};
+
+/// Object whose volume a [`VolumeController`] sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VolumeTarget {
+    Sink(u32),
+    Source(u32),
+    SinkInput(u32),
+    SourceOutput(u32),
+}
+
+#[derive(Default)]
+struct State {
+    /// Latest requested volume not yet sent to the server.
+    pending: Option<f64>,
+    /// Whether a request is in flight.
+    busy: bool,
+    settled_handlers: Vec<Rc<dyn Fn(bool)>>,
+}
+
+/// Resets `busy` even if the request driving the controller gets dropped.
+struct BusyGuard(Rc<RefCell<State>>);
+
+impl Drop for BusyGuard {
+    fn drop(&mut self) {
+        self.0.borrow_mut().busy = false;
+    }
+}
+
+/// Sets the volume of one object without flooding the server.
+///
+/// Each request scales the current volumes of the object, so it works for any number of channels
+/// and keeps balance and fade. Only one request is in flight at a time. Values requested meanwhile replace each other, and
+/// the latest one is sent as soon as the server answers, so the final value of a slider drag is
+/// never lost.
+///
+/// ```no_run
+/// use pulse_async::volume::{VolumeController, VolumeTarget};
+///
+/// # async fn f(context: pulse_async::Context) {
+/// let controller = VolumeController::new(context, VolumeTarget::Sink(0));
+/// controller.connect_settled(|success| println!("server caught up: {}", success));
+///
+/// // 50% is replaced before it is sent, only 75% reaches the server
+/// futures::join!(controller.set(50.0), controller.set(75.0));
+/// # }
+/// ```
+pub struct VolumeController<B: Backend = Context> {
+    backend: B,
+    target: VolumeTarget,
+    state: Rc<RefCell<State>>,
+}
+
+impl<B: Backend + Clone> Clone for VolumeController<B> {
+    fn clone(&self) -> Self {
+        Self {
+            backend: self.backend.clone(),
+            target: self.target,
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<B: Backend + Clone + 'static> VolumeController<B> {
+    pub fn new(backend: B, target: VolumeTarget) -> Self {
+        Self {
+            backend,
+            target,
+            state: Default::default(),
+        }
+    }
+
+    pub fn target(&self) -> VolumeTarget {
+        self.target
+    }
+
+    /// Whether requested volumes have not reached the server yet.
+    ///
+    /// UIs should not overwrite user input with server state while this is `true`.
+    pub fn is_busy(&self) -> bool {
+        let state = self.state.borrow();
+        state.busy || state.pending.is_some()
+    }
+
+    /// Requests `volume` in percent, as in [`super::VolumeLevel::from_percent`].
+    ///
+    /// The returned future has to be polled for anything to be sent. It resolves with
+    /// `Some(success)` of the last request sent if it ended up driving the controller, or `None`
+    /// if the value was handed over to a request already in flight.
+    pub fn set(&self, volume: f64) -> impl Future<Output = Option<bool>> + 'static {
+        self.state.borrow_mut().pending = Some(volume);
+
+        let this = self.clone();
+        async move { this.drive().await }
+    }
+
+    /// Calls `cb` with the result of the last request each time the server has caught up with
+    /// the latest requested volume.
+    pub fn connect_settled<F: Fn(bool) + 'static>(&self, cb: F) {
+        self.state.borrow_mut().settled_handlers.push(Rc::new(cb));
+    }
+
+    async fn drive(&self) -> Option<bool> {
+        {
+            let mut state = self.state.borrow_mut();
+
+            if state.busy || state.pending.is_none() {
+                return None;
+            }
+
+            state.busy = true;
+        }
+
+        let guard = BusyGuard(self.state.clone());
+        let mut success = true;
+
+        loop {
+            let volume = match self.state.borrow_mut().pending.take() {
+                Some(volume) => volume,
+                None => break,
+            };
+
+            success = match self.current_volumes().await {
+                Ok(mut volumes) => {
+                    volumes.set_level(VolumeLevel::from_percent(volume));
+                    self.apply_volumes(&volumes).await
+                }
+                Err(()) => false,
+            };
+        }
+
+        drop(guard);
+
+        // Handlers may call `set` again, so don't keep the state borrowed
+        let handlers = self.state.borrow().settled_handlers.clone();
+        for handler in handlers {
+            handler(success);
+        }
+
+        Some(success)
+    }
+
+    async fn current_volumes(&self) -> Result<ChannelVolumes, ()> {
+        match self.target {
+            VolumeTarget::Sink(id) => self.backend.sink(id).await.map(|info| info.volume),
+            VolumeTarget::Source(id) => self.backend.source(id).await.map(|info| info.volume),
+            VolumeTarget::SinkInput(id) => {
+                self.backend.sink_input(id).await.map(|info| info.volume)
+            }
+            VolumeTarget::SourceOutput(id) => {
+                self.backend.source_output(id).await.map(|info| info.volume)
+            }
+        }
+    }
+
+    async fn apply_volumes(&self, volumes: &ChannelVolumes) -> bool {
+        match self.target {
+            VolumeTarget::Sink(id) => self.backend.set_sink_volumes(id, volumes).await,
+            VolumeTarget::Source(id) => self.backend.set_source_volumes(id, volumes).await,
+            VolumeTarget::SinkInput(id) => self.backend.set_sink_input_volumes(id, volumes).await,
+            VolumeTarget::SourceOutput(id) => {
+                self.backend.set_source_output_volumes(id, volumes).await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use futures::{FutureExt, StreamExt};
+    use pulse::{
+        context::subscribe::{Facility, InterestMaskSet, Operation},
+        volume::Volume,
+    };
+
+    use super::*;
+    use crate::{
+        backend::fake::{self, FakeBackend},
+        test_server::TestServer,
+    };
+
+    #[test]
+    fn coalesces() {
+        let backend = FakeBackend::new();
+        backend.add_sink(fake::sink(0, "speakers"));
+        let mut events = backend.subscribe(InterestMaskSet::SINK);
+
+        let controller = VolumeController::new(backend.clone(), VolumeTarget::Sink(0));
+
+        let settled = Rc::new(Cell::new(0));
+        controller.connect_settled({
+            let settled = settled.clone();
+            move |success| {
+                assert!(success);
+                settled.set(settled.get() + 1);
+            }
+        });
+
+        let a = controller.set(25.0);
+        let b = controller.set(50.0);
+        assert!(controller.is_busy());
+
+        futures::executor::block_on(async {
+            let (a, b) = futures::join!(a, b);
+            assert_eq!(a, Some(true));
+            assert_eq!(b, None);
+        });
+
+        assert!(!controller.is_busy());
+        assert_eq!(settled.get(), 1);
+
+        // Only the latest value was sent
+        let event = events.next().now_or_never().flatten().unwrap().unwrap();
+        assert_eq!(event, (Some(Facility::Sink), Some(Operation::Changed), 0));
+        assert!(events.next().now_or_never().is_none());
+
+        futures::executor::block_on(async {
+            let info = backend.sink(0).await.unwrap();
+            assert_eq!(info.volume.max().0, Volume::NORMAL.0 / 2);
+        });
+    }
+
+    #[test]
+    fn keeps_channels() {
+        let backend = FakeBackend::new();
+
+        // Most capture streams are mono
+        let mut output = fake::source_output(0, 0, "recorder");
+        output.channel_map.init_mono();
+        output.volume.set(1, Volume::NORMAL);
+        backend.add_source_output(output);
+
+        let mut sink = fake::sink(0, "speakers");
+        assert!(ChannelVolumesExt::set_balance(
+            &mut sink.volume,
+            &sink.channel_map,
+            0.5
+        ));
+        let balance = sink.volume.balance(&sink.channel_map);
+        backend.add_sink(sink);
+
+        futures::executor::block_on(async {
+            let controller = VolumeController::new(backend.clone(), VolumeTarget::SourceOutput(0));
+            assert_eq!(controller.set(50.0).await, Some(true));
+
+            let info = backend.source_output(0).await.unwrap();
+            assert_eq!(info.volume.len(), 1);
+            assert_eq!(info.volume.max().0, Volume::NORMAL.0 / 2);
+
+            let controller = VolumeController::new(backend.clone(), VolumeTarget::Sink(0));
+            assert_eq!(controller.set(50.0).await, Some(true));
+
+            let info = backend.sink(0).await.unwrap();
+            assert_eq!(info.volume.max().0, Volume::NORMAL.0 / 2);
+            assert!((info.volume.balance(&info.channel_map) - balance).abs() < 0.01);
+        });
+    }
+
+    #[test]
+    fn mono_source_output() {
+        let server = TestServer::spawn();
+
+        futures::executor::block_on(async {
+            let context = server.connect().await;
+
+            let sink = context.introspect().sink_list().await.unwrap().remove(0);
+
+            // Peak streams record one channel, like most capture streams
+            let mut record = context.crate_stream(sink.monitor_source, None, false);
+            record.next().await.unwrap();
+
+            let output = context
+                .introspect()
+                .source_output_list()
+                .await
+                .unwrap()
+                .remove(0);
+            assert_eq!(output.volume.len(), 1);
+
+            let controller =
+                VolumeController::new(context.clone(), VolumeTarget::SourceOutput(output.index));
+            assert_eq!(controller.set(50.0).await, Some(true));
+
+            let info = context
+                .introspect()
+                .source_output(output.index)
+                .await
+                .unwrap();
+            assert_eq!(info.volume.max(), Volume(Volume::NORMAL.0 / 2));
+        });
+    }
+
+    #[test]
+    fn failure() {
+        let backend = FakeBackend::new();
+        let controller = VolumeController::new(backend, VolumeTarget::SinkInput(3));
+
+        let result = futures::executor::block_on(controller.set(10.0));
+        assert_eq!(result, Some(false));
+        assert!(!controller.is_busy());
+    }
+}
@@ -0,0 +1,241 @@
+//! Volume conversions and per-channel helpers.
+//!
+//! PulseAudio's raw [`Volume`] is on a cubic scale: `Volume::NORMAL` is 100%, the amplitude factor
+//! is the cube of `volume / Volume::NORMAL`. Sliders should use [`VolumeLevel::percent`] (or
+//! [`VolumeLevel::cubic`]) so they feel natural, meters and DSP want [`VolumeLevel::linear`] or
+//! [`VolumeLevel::db`].
+
+use pulse::{
+    channelmap,
+    volume::{ChannelVolumes, Volume},
+};
+
+mod controller;
+pub use controller::{VolumeController, VolumeTarget};
+
+/// A single volume value convertible between the scales in use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct VolumeLevel(Volume);
+
+// `Volume` itself does not implement `Hash`
+impl std::hash::Hash for VolumeLevel {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0 .0.hash(state);
+    }
+}
+
+impl Default for VolumeLevel {
+    fn default() -> Self {
+        Self::NORMAL
+    }
+}
+
+impl VolumeLevel {
+    pub const MUTED: Self = Self(Volume::MUTED);
+    pub const NORMAL: Self = Self(Volume::NORMAL);
+
+    fn from_raw(raw: f64) -> Self {
+        let raw = raw
+            .round()
+            .clamp(Volume::MUTED.0 as f64, Volume::MAX.0 as f64);
+        Self(Volume(raw as u32))
+    }
+
+    pub fn from_volume(volume: Volume) -> Self {
+        Self(Volume(volume.0.min(Volume::MAX.0)))
+    }
+
+    pub fn volume(self) -> Volume {
+        self.0
+    }
+
+    /// `100.0` is [`Volume::NORMAL`].
+    pub fn from_percent(percent: f64) -> Self {
+        Self::from_raw(Volume::NORMAL.0 as f64 * percent / 100.0)
+    }
+
+    pub fn percent(self) -> f64 {
+        self.cubic() * 100.0
+    }
+
+    /// PulseAudio's native scale, `1.0` is [`Volume::NORMAL`].
+    pub fn from_cubic(cubic: f64) -> Self {
+        Self::from_raw(Volume::NORMAL.0 as f64 * cubic)
+    }
+
+    pub fn cubic(self) -> f64 {
+        self.0 .0 as f64 / Volume::NORMAL.0 as f64
+    }
+
+    /// Amplitude factor applied to samples.
+    pub fn from_linear(linear: f64) -> Self {
+        Self::from_cubic(linear.max(0.0).cbrt())
+    }
+
+    pub fn linear(self) -> f64 {
+        self.cubic().powi(3)
+    }
+
+    /// Gain in decibels, `f64::NEG_INFINITY` maps to [`VolumeLevel::MUTED`].
+    pub fn from_db(db: f64) -> Self {
+        if db == f64::NEG_INFINITY {
+            return Self::MUTED;
+        }
+
+        Self::from_cubic(10f64.powf(db / 60.0))
+    }
+
+    /// Gain in decibels, `f64::NEG_INFINITY` when muted.
+    pub fn db(self) -> f64 {
+        if self.0 == Volume::MUTED {
+            return f64::NEG_INFINITY;
+        }
+
+        60.0 * self.cubic().log10()
+    }
+
+    /// Keeps the level within what a device can actually represent.
+    ///
+    /// Devices reporting `n_volume_steps` only support that many evenly spaced volumes between
+    /// muted and `base_volume`; others accept anything up to [`Volume::MAX`].
+    pub fn clamp_to_device(self, base_volume: Volume, n_volume_steps: u32) -> Self {
+        if n_volume_steps < 2 || base_volume == Volume::MUTED {
+            return self;
+        }
+
+        let step = base_volume.0 as f64 / (n_volume_steps - 1) as f64;
+        let raw = self.0 .0.min(base_volume.0) as f64;
+
+        Self::from_raw((raw / step).round() * step)
+    }
+}
+
+impl From<Volume> for VolumeLevel {
+    fn from(volume: Volume) -> Self {
+        Self::from_volume(volume)
+    }
+}
+
+impl From<VolumeLevel> for Volume {
+    fn from(level: VolumeLevel) -> Self {
+        level.volume()
+    }
+}
+
+/// Level, balance and fade of a [`ChannelVolumes`].
+///
+/// Balance goes from `-1.0` (left only) to `1.0` (right only), fade from `-1.0` (rear only) to
+/// `1.0` (front only). Both are `0.0` for maps without left/right or front/rear channels.
+pub trait ChannelVolumesExt {
+    /// Loudest channel.
+    fn level(&self) -> VolumeLevel;
+
+    /// Scales all channels so the loudest one ends up at `level`, keeping balance and fade.
+    fn set_level(&mut self, level: VolumeLevel);
+
+    fn balance(&self, map: &channelmap::Map) -> f32;
+
+    /// Returns `false` if the volumes don't match the map.
+    fn set_balance(&mut self, map: &channelmap::Map, balance: f32) -> bool;
+
+    fn fade(&self, map: &channelmap::Map) -> f32;
+
+    /// Returns `false` if the volumes don't match the map.
+    fn set_fade(&mut self, map: &channelmap::Map, fade: f32) -> bool;
+}
+
+impl ChannelVolumesExt for ChannelVolumes {
+    fn level(&self) -> VolumeLevel {
+        VolumeLevel::from_volume(self.max())
+    }
+
+    fn set_level(&mut self, level: VolumeLevel) {
+        if self.max() == Volume::MUTED {
+            // Nothing to scale from, fall back to a flat volume
+            let len = self.len().max(1);
+            self.set(len, level.volume());
+        } else {
+            self.scale(level.volume());
+        }
+    }
+
+    fn balance(&self, map: &channelmap::Map) -> f32 {
+        self.get_balance(map)
+    }
+
+    fn set_balance(&mut self, map: &channelmap::Map, balance: f32) -> bool {
+        ChannelVolumes::set_balance(self, map, balance.clamp(-1.0, 1.0)).is_some()
+    }
+
+    fn fade(&self, map: &channelmap::Map) -> f32 {
+        self.get_fade(map)
+    }
+
+    fn set_fade(&mut self, map: &channelmap::Map, fade: f32) -> bool {
+        ChannelVolumes::set_fade(self, map, fade.clamp(-1.0, 1.0)).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn conversions() {
+        let normal = VolumeLevel::NORMAL;
+        assert!(close(normal.percent(), 100.0));
+        assert!(close(normal.linear(), 1.0));
+        assert!(close(normal.db(), 0.0));
+
+        let half = VolumeLevel::from_percent(50.0);
+        assert_eq!(half.volume(), Volume(Volume::NORMAL.0 / 2));
+        assert!(close(half.linear(), 0.125));
+        assert!(close(half.db(), 60.0 * 0.5f64.log10()));
+
+        assert!(close(VolumeLevel::from_linear(0.125).percent(), 50.0));
+        assert!(close(VolumeLevel::from_db(-6.0).db(), -6.0));
+
+        assert_eq!(VolumeLevel::from_db(f64::NEG_INFINITY), VolumeLevel::MUTED);
+        assert_eq!(VolumeLevel::MUTED.db(), f64::NEG_INFINITY);
+        assert_eq!(VolumeLevel::from_percent(-10.0), VolumeLevel::MUTED);
+        assert_eq!(VolumeLevel::from_percent(1e12).volume(), Volume::MAX);
+    }
+
+    #[test]
+    fn clamp_to_device() {
+        let level = VolumeLevel::from_percent(150.0);
+        assert_eq!(level.clamp_to_device(Volume::NORMAL, 0), level);
+
+        // 0, 25, 50, 75 and 100%
+        let stepped = VolumeLevel::from_percent(60.0).clamp_to_device(Volume::NORMAL, 5);
+        assert_eq!(stepped, VolumeLevel::from_percent(50.0));
+
+        let capped = level.clamp_to_device(Volume::NORMAL, 5);
+        assert_eq!(capped, VolumeLevel::NORMAL);
+    }
+
+    #[test]
+    fn balance_and_level() {
+        let mut map = channelmap::Map::default();
+        map.init_stereo();
+
+        let mut volumes = ChannelVolumes::default();
+        volumes.set(2, Volume::NORMAL);
+        assert_eq!(volumes.balance(&map), 0.0);
+
+        assert!(ChannelVolumesExt::set_balance(&mut volumes, &map, 0.5));
+        assert!((volumes.balance(&map) - 0.5).abs() < 1e-3);
+        assert_eq!(volumes.level(), VolumeLevel::NORMAL);
+
+        volumes.set_level(VolumeLevel::from_percent(50.0));
+        assert_eq!(volumes.level(), VolumeLevel::from_percent(50.0));
+        assert!((volumes.balance(&map) - 0.5).abs() < 1e-2);
+
+        // Stereo has no rear channels
+        assert_eq!(volumes.fade(&map), 0.0);
+    }
+}
@@ -7,11 +7,18 @@ use gtk::{gio, glib};
 use crate::config::VERSION;
 use crate::CathodeWindow;
 
+const MAX_RECENT_SERVERS: usize = 10;
+
 mod imp {
+    use std::cell::RefCell;
+
     use super::*;
 
     #[derive(Debug, Default)]
-    pub struct CathodeApplication {}
+    pub struct CathodeApplication {
+        /// Server passed with `--server`.
+        pub server: RefCell<Option<String>>,
+    }
 
     #[glib::object_subclass]
     impl ObjectSubclass for CathodeApplication {
@@ -24,6 +31,15 @@ mod imp {
         fn constructed(&self, obj: &Self::Type) {
             self.parent_constructed(obj);
 
+            obj.add_main_option(
+                "server",
+                glib::Char::from(b's'),
+                glib::OptionFlags::NONE,
+                glib::OptionArg::String,
+                "Sound server to connect to, e.g. unix:/path/to/socket or tcp:host:port",
+                Some("SERVER"),
+            );
+
             obj.setup_gactions();
             obj.set_accels_for_action("app.quit", &["<primary>q"]);
             obj.set_accels_for_action("app.about", &["<primary>a"]);
@@ -31,18 +47,34 @@ mod imp {
     }
 
     impl ApplicationImpl for CathodeApplication {
+        fn handle_local_options(
+            &self,
+            application: &Self::Type,
+            options: &glib::VariantDict,
+        ) -> i32 {
+            if let Some(server) = options
+                .lookup_value("server", None)
+                .and_then(|server| server.get::<String>())
+            {
+                *self.server.borrow_mut() = Some(server);
+            }
+
+            self.parent_handle_local_options(application, options)
+        }
+
         fn activate(&self, application: &Self::Type) {
             // Get the current window or create one if necessary
-            let window = if let Some(window) = application.active_window() {
-                window
+            if let Some(window) = application.active_window() {
+                window.present();
             } else {
-                let window = CathodeWindow::new(application);
-                crate::run::run(window.clone());
-                window.upcast()
-            };
+                let server = self
+                    .server
+                    .borrow()
+                    .clone()
+                    .or_else(|| std::env::var("PULSE_SERVER").ok());
 
-            // Ask the window manager/compositor to present the window
-            window.present();
+                application.open_window(server);
+            }
         }
     }
 
@@ -62,6 +94,65 @@ impl CathodeApplication {
             .expect("Failed to create CathodeApplication")
     }
 
+    /// Opens a new window connected to `server`, or to the default server if `None`.
+    pub fn open_window(&self, server: Option<String>) -> CathodeWindow {
+        let window = CathodeWindow::new(self);
+        window.add_server(server);
+
+        // Ask the window manager/compositor to present the window
+        window.present();
+
+        window
+    }
+
+    /// `None` when the schema is not installed, e.g. when running from the source tree.
+    fn settings(&self) -> Option<gio::Settings> {
+        let schema = gio::SettingsSchemaSource::default()?.lookup(&self.application_id()?, true)?;
+
+        Some(gio::Settings::new_full(
+            &schema,
+            None::<&gio::SettingsBackend>,
+            None,
+        ))
+    }
+
+    /// Servers picked in the connect dialog, most recent first.
+    pub fn recent_servers(&self) -> Vec<String> {
+        self.settings()
+            .map(|settings| {
+                settings
+                    .strv("recent-servers")
+                    .iter()
+                    .map(|server| server.to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn add_recent_server(&self, server: &str) {
+        let mut recent = self.recent_servers();
+        recent.retain(|s| s != server);
+        recent.insert(0, server.to_string());
+        recent.truncate(MAX_RECENT_SERVERS);
+
+        let recent: Vec<&str> = recent.iter().map(String::as_str).collect();
+        if let Some(settings) = self.settings() {
+            settings.set_strv("recent-servers", &recent).ok();
+        }
+    }
+
+    /// Whether input devices include the monitors of outputs.
+    pub fn show_monitors(&self) -> bool {
+        self.settings()
+            .map_or(false, |settings| settings.boolean("show-monitors"))
+    }
+
+    pub fn set_show_monitors(&self, show: bool) {
+        if let Some(settings) = self.settings() {
+            settings.set_boolean("show-monitors", show).ok();
+        }
+    }
+
     fn setup_gactions(&self) {
         let quit_action = gio::SimpleAction::new("quit", None);
         quit_action.connect_activate(clone!(@weak self as app => move |_, _| {
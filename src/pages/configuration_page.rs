@@ -0,0 +1,237 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use glib::clone;
+
+use std::cell::RefCell;
+
+use gtk::CompositeTemplate;
+use once_cell::unsync::OnceCell;
+
+use pulse_async::device_manager::ROLES;
+use pulse_async::DeviceManagerEntry;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(file = "configuration_page.ui")]
+    pub struct ConfigurationPage {
+        #[template_child]
+        pub output_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub output_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub input_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub input_list: TemplateChild<gtk::ListBox>,
+
+        pub context: OnceCell<pulse_async::Context>,
+        /// Known devices, most preferred first.
+        pub devices: RefCell<Vec<DeviceManagerEntry>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ConfigurationPage {
+        const NAME: &'static str = "ConfigurationPage";
+        type Type = super::ConfigurationPage;
+        type ParentType = adw::Bin;
+        type Interfaces = (gtk::Buildable,);
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ConfigurationPage {}
+    impl WidgetImpl for ConfigurationPage {}
+    impl BuildableImpl for ConfigurationPage {}
+    impl BinImpl for ConfigurationPage {}
+}
+
+glib::wrapper! {
+    pub struct ConfigurationPage(ObjectSubclass<imp::ConfigurationPage>) @extends gtk::Widget;
+}
+
+fn clear_list(list: &gtk::ListBox) {
+    while let Some(child) = list.first_child() {
+        list.remove(&child);
+    }
+}
+
+impl ConfigurationPage {
+    pub fn new() -> Self {
+        glib::Object::new(&[]).expect("Failed to create ConfigurationPage")
+    }
+
+    pub fn init_context(&self, context: pulse_async::Context) {
+        self.imp().context.set(context).unwrap()
+    }
+
+    /// Shows `devices` ordered by their priority for streams without a role.
+    pub fn set_devices(&self, devices: &[DeviceManagerEntry]) {
+        let mut devices = devices.to_vec();
+        devices.sort_by(|a, b| {
+            let priority =
+                |device: &DeviceManagerEntry| device.priority("none").unwrap_or(u32::MAX);
+            priority(a)
+                .cmp(&priority(b))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        *self.imp().devices.borrow_mut() = devices;
+        self.rebuild();
+    }
+
+    /// Tells the user there is nothing to configure without module-device-manager.
+    pub fn set_unavailable(&self) {
+        let imp = self.imp();
+
+        imp.devices.borrow_mut().clear();
+        self.rebuild();
+
+        imp.output_group.set_description(Some(
+            "Load module-device-manager to choose preferred devices",
+        ));
+        imp.output_group.set_visible(true);
+    }
+
+    fn rebuild(&self) {
+        let imp = self.imp();
+
+        clear_list(&imp.output_list);
+        clear_list(&imp.input_list);
+
+        let devices = imp.devices.borrow();
+
+        for is_sink in [true, false] {
+            let list = if is_sink {
+                &imp.output_list
+            } else {
+                &imp.input_list
+            };
+
+            let names: Vec<&str> = devices
+                .iter()
+                .filter(|device| device.is_sink() == is_sink)
+                .map(|device| device.name.as_str())
+                .collect();
+
+            for (position, name) in names.iter().enumerate() {
+                let device = devices.iter().find(|device| device.name == *name).unwrap();
+                list.append(&self.build_row(device, position, names.len()));
+            }
+        }
+
+        imp.output_group
+            .set_visible(devices.iter().any(|device| device.is_sink()));
+        imp.input_group
+            .set_visible(devices.iter().any(|device| !device.is_sink()));
+    }
+
+    fn build_row(
+        &self,
+        device: &DeviceManagerEntry,
+        position: usize,
+        len: usize,
+    ) -> adw::ActionRow {
+        let title = device
+            .description
+            .as_deref()
+            .unwrap_or(device.device_name());
+        let row = adw::ActionRow::builder()
+            .title(&glib::markup_escape_text(title))
+            .build();
+
+        if device.index.is_none() {
+            row.set_subtitle("Not connected");
+        }
+
+        if let Some(icon) = device.icon.as_deref() {
+            row.add_prefix(&gtk::Image::from_icon_name(Some(icon)));
+        }
+
+        for (icon, tooltip, up) in [
+            ("go-up-symbolic", "Prefer", true),
+            ("go-down-symbolic", "Prefer Less", false),
+        ] {
+            let button = gtk::Button::builder()
+                .icon_name(icon)
+                .tooltip_text(tooltip)
+                .valign(gtk::Align::Center)
+                .css_classes(vec!["flat".to_string()])
+                .sensitive(if up { position > 0 } else { position + 1 < len })
+                .build();
+
+            let name = device.name.clone();
+            button.connect_clicked(clone!(@weak self as page => move |_| {
+                page.move_device(&name, up);
+            }));
+
+            row.add_suffix(&button);
+        }
+
+        row
+    }
+
+    /// Swaps a device with its more (`up`) or less preferred neighbour of the same kind.
+    fn move_device(&self, name: &str, up: bool) {
+        let imp = self.imp();
+
+        let order: Vec<String> = {
+            let mut devices = imp.devices.borrow_mut();
+
+            let is_sink = match devices.iter().find(|device| device.name == name) {
+                Some(device) => device.is_sink(),
+                None => return,
+            };
+
+            let positions: Vec<usize> = devices
+                .iter()
+                .enumerate()
+                .filter(|(_, device)| device.is_sink() == is_sink)
+                .map(|(i, _)| i)
+                .collect();
+
+            let current = match positions.iter().position(|i| devices[*i].name == name) {
+                Some(current) => current,
+                None => return,
+            };
+
+            let other = if up {
+                current.checked_sub(1)
+            } else {
+                Some(current + 1).filter(|other| *other < positions.len())
+            };
+
+            match other {
+                Some(other) => devices.swap(positions[current], positions[other]),
+                None => return,
+            }
+
+            positions.iter().map(|i| devices[*i].name.clone()).collect()
+        };
+
+        self.rebuild();
+
+        let context = match imp.context.get() {
+            Some(context) => context.clone(),
+            None => return,
+        };
+
+        glib::MainContext::default().spawn_local(async move {
+            let order: Vec<&str> = order.iter().map(String::as_str).collect();
+            let mut manager = context.device_manager();
+
+            manager.enable_role_device_priority_routing(true).await;
+
+            // The list applies to every role, so a role can't keep preferring another device
+            for role in ROLES {
+                manager.reorder_devices_for_role(role, &order).await;
+            }
+        });
+    }
+}
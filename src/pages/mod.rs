@@ -3,3 +3,15 @@ pub use playback_page::PlaybackPage;
 
 pub mod output_page;
 pub use output_page::OutputPage;
+
+pub mod restore_page;
+pub use restore_page::RestorePage;
+
+pub mod configuration_page;
+pub use configuration_page::ConfigurationPage;
+
+pub mod server_page;
+pub use server_page::ServerPage;
+
+#[cfg(test)]
+mod tests;
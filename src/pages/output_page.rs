@@ -8,12 +8,14 @@ use std::{
     collections::HashMap,
 };
 
+use glib::clone;
 use gtk::{subclass::prelude::ObjectSubclassIsExt, CompositeTemplate};
 
 use pulse::context::subscribe::Operation;
-use pulse_async::SinkInfo;
+use pulse_async::backend::Backend;
+use pulse_async::{SinkInfo, SourceInfo};
 
-use crate::widgets::SinkItem;
+use crate::widgets::{SinkItem, SourceItem};
 
 mod imp {
     use super::*;
@@ -23,8 +25,16 @@ mod imp {
     pub struct OutputPage {
         #[template_child]
         pub flow_box: TemplateChild<gtk::FlowBox>,
+        #[template_child]
+        pub input_flow_box: TemplateChild<gtk::FlowBox>,
+        #[template_child]
+        pub monitors_switch: TemplateChild<gtk::Switch>,
 
         pub items: RefCell<HashMap<u32, SinkItem>>,
+        pub source_items: RefCell<HashMap<u32, SourceItem>>,
+
+        pub default_sink: RefCell<Option<String>>,
+        pub default_source: RefCell<Option<String>>,
     }
 
     #[glib::object_subclass]
@@ -43,7 +53,26 @@ mod imp {
         }
     }
 
-    impl ObjectImpl for OutputPage {}
+    impl ObjectImpl for OutputPage {
+        fn constructed(&self, obj: &Self::Type) {
+            self.parent_constructed(obj);
+
+            let switch = self.monitors_switch.get();
+            self.input_flow_box.set_filter_func(move |child| {
+                let monitor = child
+                    .child()
+                    .and_then(|item| item.downcast::<SourceItem>().ok())
+                    .map_or(false, |item| item.is_monitor());
+
+                !monitor || switch.is_active()
+            });
+
+            self.monitors_switch
+                .connect_active_notify(clone!(@weak obj => move |_| {
+                    obj.imp().input_flow_box.invalidate_filter();
+                }));
+        }
+    }
     impl WidgetImpl for OutputPage {}
     impl BuildableImpl for OutputPage {}
     impl BinImpl for OutputPage {}
@@ -62,15 +91,20 @@ impl OutputPage {
         self.imp().items.borrow_mut()
     }
 
-    pub async fn event(&self, context: &pulse_async::Context, op: &Operation, id: u32) {
+    pub async fn event<B: Backend>(&self, backend: &B, op: &Operation, id: u32) {
         match op {
             Operation::New => {
-                if let Ok(info) = context.introspect().sink(id).await {
+                if let Ok(info) = backend.sink(id).await {
                     self.add_item(&info);
                 }
             }
             Operation::Changed => {
-                if let Ok(info) = context.introspect().sink(id).await {
+                // Items are only created for `New`, which wires them up
+                if !self.imp().items.borrow().contains_key(&id) {
+                    return;
+                }
+
+                if let Ok(info) = backend.sink(id).await {
                     self.add_item(&info);
                 }
             }
@@ -99,6 +133,7 @@ impl OutputPage {
         };
 
         item.update(info);
+        item.set_default(item.name() == *self.imp().default_sink.borrow());
 
         item
     }
@@ -108,4 +143,113 @@ impl OutputPage {
             self.imp().flow_box.remove(&item);
         }
     }
+
+    pub async fn source_event<B: Backend>(&self, backend: &B, op: &Operation, id: u32) {
+        match op {
+            Operation::New => {
+                if let Ok(info) = backend.source(id).await {
+                    self.add_source_item(&info);
+                }
+            }
+            Operation::Changed => {
+                if !self.imp().source_items.borrow().contains_key(&id) {
+                    return;
+                }
+
+                if let Ok(info) = backend.source(id).await {
+                    self.add_source_item(&info);
+                }
+            }
+            Operation::Removed => {
+                self.remove_source_item(id);
+            }
+        }
+    }
+
+    pub fn add_source_item(&self, info: &SourceInfo) -> SourceItem {
+        let id = info.index;
+
+        let item = {
+            let mut items = self.imp().source_items.borrow_mut();
+
+            if let Some(item) = items.get(&id) {
+                item.clone()
+            } else {
+                let item = SourceItem::new();
+
+                self.imp().input_flow_box.get().append(&item);
+                items.insert(id, item.clone());
+
+                item
+            }
+        };
+
+        item.update(info);
+        item.set_default(item.name() == *self.imp().default_source.borrow());
+
+        // A source may turn out to be a monitor only once its info is known
+        self.imp().input_flow_box.invalidate_filter();
+
+        item
+    }
+
+    pub fn remove_source_item(&self, id: u32) {
+        if let Some(item) = self.imp().source_items.borrow_mut().remove(&id) {
+            self.imp().input_flow_box.remove(&item);
+        }
+    }
+
+    pub fn show_monitors(&self) -> bool {
+        self.imp().monitors_switch.is_active()
+    }
+
+    /// Includes or excludes sources recording what a sink plays.
+    pub fn set_show_monitors(&self, show: bool) {
+        self.imp().monitors_switch.set_active(show);
+    }
+
+    pub fn connect_show_monitors_changed<F>(&self, cb: F)
+    where
+        F: Fn(bool) + 'static,
+    {
+        self.imp()
+            .monitors_switch
+            .connect_active_notify(move |switch| cb(switch.is_active()));
+    }
+
+    /// Refreshes the shown sinks and sources of card `id`, whose profile and port changes show
+    /// up in them.
+    pub async fn card_changed<B: Backend>(&self, backend: &B, id: u32) {
+        for sink in backend.sink_list().await.unwrap_or_default() {
+            let shown = self.imp().items.borrow().contains_key(&sink.index);
+
+            if sink.card == Some(id) && shown {
+                self.add_item(&sink);
+            }
+        }
+
+        for source in backend.source_list().await.unwrap_or_default() {
+            let shown = self.imp().source_items.borrow().contains_key(&source.index);
+
+            if source.card == Some(id) && shown {
+                self.add_source_item(&source);
+            }
+        }
+    }
+
+    /// Marks the devices new streams and recordings use by default.
+    pub fn set_defaults(&self, sink: Option<&str>, source: Option<&str>) {
+        let imp = self.imp();
+
+        *imp.default_sink.borrow_mut() = sink.map(str::to_string);
+        *imp.default_source.borrow_mut() = source.map(str::to_string);
+
+        for item in imp.items.borrow().values() {
+            item.set_default(item.name().as_deref() == sink);
+        }
+
+        for item in imp.source_items.borrow().values() {
+            item.set_default(item.name().as_deref() == source);
+        }
+    }
 }
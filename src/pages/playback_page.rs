@@ -10,9 +10,11 @@ use std::{
 
 use gtk::{subclass::prelude::ObjectSubclassIsExt, CompositeTemplate};
 
-use pulse_async::SinkInputInfo;
+use pulse::context::subscribe::Operation;
+use pulse_async::backend::Backend;
+use pulse_async::{SinkInputInfo, SourceOutputInfo};
 
-use crate::widgets::SinkInputItem;
+use crate::widgets::{SinkInputItem, SourceOutputItem};
 
 mod imp {
     use super::*;
@@ -27,6 +29,7 @@ mod imp {
         pub input_flow_box: TemplateChild<gtk::FlowBox>,
 
         pub items: RefCell<HashMap<u32, SinkInputItem>>,
+        pub input_items: RefCell<HashMap<u32, SourceOutputItem>>,
     }
 
     #[glib::object_subclass]
@@ -64,6 +67,33 @@ impl PlaybackPage {
         self.imp().items.borrow_mut()
     }
 
+    pub fn input_items(&self) -> RefMut<HashMap<u32, SourceOutputItem>> {
+        self.imp().input_items.borrow_mut()
+    }
+
+    pub async fn event<B: Backend>(&self, backend: &B, op: &Operation, id: u32) {
+        match op {
+            Operation::New => {
+                if let Ok(info) = backend.sink_input(id).await {
+                    self.add_item(&info);
+                }
+            }
+            Operation::Changed => {
+                // Items are only created for `New`, which wires them up
+                if !self.imp().items.borrow().contains_key(&id) {
+                    return;
+                }
+
+                if let Ok(info) = backend.sink_input(id).await {
+                    self.add_item(&info);
+                }
+            }
+            Operation::Removed => {
+                self.remove_item(id);
+            }
+        }
+    }
+
     pub fn add_item(&self, info: &SinkInputInfo) -> SinkInputItem {
         let id = info.index;
 
@@ -92,4 +122,73 @@ impl PlaybackPage {
             self.imp().flow_box.remove(&item);
         }
     }
+
+    /// Updates or removes a shown recording stream.
+    ///
+    /// New streams are left to the caller, which decides which ones to show.
+    pub async fn input_event<B: Backend>(&self, backend: &B, op: &Operation, id: u32) {
+        match op {
+            Operation::New => {}
+            Operation::Changed => {
+                if !self.imp().input_items.borrow().contains_key(&id) {
+                    return;
+                }
+
+                if let Ok(info) = backend.source_output(id).await {
+                    self.add_input_item(&info);
+                }
+            }
+            Operation::Removed => {
+                self.remove_input_item(id);
+            }
+        }
+    }
+
+    /// Refreshes the shown streams of client `id`, applications are named after their client.
+    pub async fn client_changed<B: Backend>(&self, backend: &B, id: u32) {
+        for input in backend.sink_input_list().await.unwrap_or_default() {
+            let shown = self.imp().items.borrow().contains_key(&input.index);
+
+            if input.client == Some(id) && shown {
+                self.add_item(&input);
+            }
+        }
+
+        for output in backend.source_output_list().await.unwrap_or_default() {
+            let shown = self.imp().input_items.borrow().contains_key(&output.index);
+
+            if output.client == Some(id) && shown {
+                self.add_input_item(&output);
+            }
+        }
+    }
+
+    pub fn add_input_item(&self, info: &SourceOutputInfo) -> SourceOutputItem {
+        let id = info.index;
+
+        let item = {
+            let mut items = self.imp().input_items.borrow_mut();
+
+            if let Some(item) = items.get(&id) {
+                item.clone()
+            } else {
+                let item = SourceOutputItem::new();
+
+                self.imp().input_flow_box.get().append(&item);
+                items.insert(id, item.clone());
+
+                item
+            }
+        };
+
+        item.update(info);
+
+        item
+    }
+
+    pub fn remove_input_item(&self, id: u32) {
+        if let Some(item) = self.imp().input_items.borrow_mut().remove(&id) {
+            self.imp().input_flow_box.remove(&item);
+        }
+    }
 }
@@ -0,0 +1,294 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use glib::clone;
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+use gtk::CompositeTemplate;
+use once_cell::unsync::OnceCell;
+
+use pulse::proplist::UpdateMode;
+use pulse_async::stream_restore::EVENT_SOUNDS;
+use pulse_async::{ChannelVolumesExt, StreamRestoreEntry, VolumeLevel};
+
+/// Widgets of one remembered stream.
+#[derive(Debug)]
+pub struct RestoreRow {
+    row: adw::ActionRow,
+    scale: gtk::Scale,
+    mute_button: gtk::ToggleButton,
+    entry: RefCell<StreamRestoreEntry>,
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(file = "restore_page.ui")]
+    pub struct RestorePage {
+        #[template_child]
+        pub playback_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub playback_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub recording_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub recording_list: TemplateChild<gtk::ListBox>,
+
+        pub context: OnceCell<pulse_async::Context>,
+        pub rows: RefCell<HashMap<String, Rc<RestoreRow>>>,
+
+        /// Edits not written yet, by entry name.
+        pub pending: RefCell<HashMap<String, StreamRestoreEntry>>,
+        pub writing: Cell<bool>,
+        /// Set while rows are updated from the server.
+        pub updating: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for RestorePage {
+        const NAME: &'static str = "RestorePage";
+        type Type = super::RestorePage;
+        type ParentType = adw::Bin;
+        type Interfaces = (gtk::Buildable,);
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for RestorePage {}
+    impl WidgetImpl for RestorePage {}
+    impl BuildableImpl for RestorePage {}
+    impl BinImpl for RestorePage {}
+}
+
+glib::wrapper! {
+    pub struct RestorePage(ObjectSubclass<imp::RestorePage>) @extends gtk::Widget;
+}
+
+/// Human readable name of what an entry matches.
+fn entry_title(entry: &StreamRestoreEntry) -> String {
+    if entry.name == EVENT_SOUNDS {
+        return "System Sounds".to_string();
+    }
+
+    match entry.key() {
+        Some((_, value)) => value.to_string(),
+        None => entry.name.clone(),
+    }
+}
+
+impl RestorePage {
+    pub fn new() -> Self {
+        glib::Object::new(&[]).expect("Failed to create RestorePage")
+    }
+
+    pub fn init_context(&self, context: pulse_async::Context) {
+        self.imp().context.set(context).unwrap()
+    }
+
+    /// Shows `entries`, dropping rows of entries that are gone.
+    pub fn set_entries(&self, entries: &[StreamRestoreEntry]) {
+        let imp = self.imp();
+
+        let stale: Vec<String> = imp
+            .rows
+            .borrow()
+            .keys()
+            .filter(|name| !entries.iter().any(|entry| &entry.name == *name))
+            .cloned()
+            .collect();
+
+        for name in stale {
+            self.remove_row(&name);
+        }
+
+        for entry in entries {
+            let row = imp.rows.borrow().get(&entry.name).cloned();
+            let row = match row {
+                Some(row) => row,
+                None => self.add_row(entry),
+            };
+
+            // Don't override edits on their way to the server
+            if imp.writing.get() {
+                continue;
+            }
+
+            *row.entry.borrow_mut() = entry.clone();
+
+            imp.updating.set(true);
+            row.scale.set_value(entry.volume.level().percent());
+            row.mute_button.set_active(entry.mute);
+            imp.updating.set(false);
+
+            row.scale.set_sensitive(entry.volume.is_valid());
+            let device =
+                glib::markup_escape_text(entry.device.as_deref().unwrap_or("Default Device"));
+            row.row.set_subtitle(&device);
+        }
+
+        imp.playback_group
+            .set_visible(entries.iter().any(|entry| !entry.is_source_output()));
+        imp.recording_group
+            .set_visible(entries.iter().any(|entry| entry.is_source_output()));
+    }
+
+    fn add_row(&self, entry: &StreamRestoreEntry) -> Rc<RestoreRow> {
+        let imp = self.imp();
+
+        let row = adw::ActionRow::builder()
+            .title(&glib::markup_escape_text(&entry_title(entry)))
+            .build();
+
+        let scale = gtk::Scale::with_range(gtk::Orientation::Horizontal, 0.0, 100.0, 1.0);
+        scale.set_width_request(160);
+        scale.set_valign(gtk::Align::Center);
+        row.add_suffix(&scale);
+
+        let mute_button = gtk::ToggleButton::builder()
+            .icon_name("audio-volume-muted-symbolic")
+            .tooltip_text("Mute")
+            .valign(gtk::Align::Center)
+            .css_classes(vec!["flat".to_string()])
+            .build();
+        row.add_suffix(&mute_button);
+
+        let forget_button = gtk::Button::builder()
+            .icon_name("user-trash-symbolic")
+            .tooltip_text("Forget")
+            .valign(gtk::Align::Center)
+            .css_classes(vec!["flat".to_string()])
+            .build();
+        row.add_suffix(&forget_button);
+
+        let restore_row = Rc::new(RestoreRow {
+            row: row.clone(),
+            scale: scale.clone(),
+            mute_button: mute_button.clone(),
+            entry: RefCell::new(entry.clone()),
+        });
+
+        let weak_row = Rc::downgrade(&restore_row);
+        scale.connect_value_changed(
+            clone!(@weak self as page, @strong weak_row => move |scale| {
+                if let Some(row) = weak_row.upgrade() {
+                    if !page.imp().updating.get() {
+                        let mut entry = row.entry.borrow_mut();
+
+                        if !entry.volume.is_valid() {
+                            entry.channel_map.init_mono();
+                        }
+                        entry.volume.set_level(VolumeLevel::from_percent(scale.value()));
+
+                        page.write_entry(entry.clone());
+                    }
+                }
+            }),
+        );
+
+        mute_button.connect_toggled(clone!(@weak self as page => move |button| {
+            if let Some(row) = weak_row.upgrade() {
+                if !page.imp().updating.get() {
+                    let mut entry = row.entry.borrow_mut();
+                    entry.mute = button.is_active();
+
+                    page.write_entry(entry.clone());
+                }
+            }
+        }));
+
+        let name = entry.name.clone();
+        forget_button.connect_clicked(clone!(@weak self as page => move |_| {
+            page.forget(&name);
+        }));
+
+        if entry.is_source_output() {
+            imp.recording_list.append(&row);
+        } else {
+            imp.playback_list.append(&row);
+        }
+
+        imp.rows
+            .borrow_mut()
+            .insert(entry.name.clone(), restore_row.clone());
+
+        restore_row
+    }
+
+    fn remove_row(&self, name: &str) {
+        let imp = self.imp();
+
+        if let Some(row) = imp.rows.borrow_mut().remove(name) {
+            if let Some(list) = row
+                .row
+                .parent()
+                .and_then(|parent| parent.downcast::<gtk::ListBox>().ok())
+            {
+                list.remove(&row.row);
+            }
+        }
+    }
+
+    /// Queues `entry` to be written, edits made meanwhile are written together afterwards.
+    fn write_entry(&self, entry: StreamRestoreEntry) {
+        let imp = self.imp();
+
+        imp.pending.borrow_mut().insert(entry.name.clone(), entry);
+
+        if imp.writing.replace(true) {
+            return;
+        }
+
+        let context = match imp.context.get() {
+            Some(context) => context.clone(),
+            None => return,
+        };
+
+        glib::MainContext::default().spawn_local(clone!(@weak self as page => async move {
+            loop {
+                let entries: Vec<StreamRestoreEntry> = page
+                    .imp()
+                    .pending
+                    .borrow_mut()
+                    .drain()
+                    .map(|(_, entry)| entry)
+                    .collect();
+
+                if entries.is_empty() {
+                    break;
+                }
+
+                context
+                    .stream_restore()
+                    .write(&entries, UpdateMode::Replace, true)
+                    .await;
+            }
+
+            page.imp().writing.set(false);
+        }));
+    }
+
+    fn forget(&self, name: &str) {
+        let context = match self.imp().context.get() {
+            Some(context) => context.clone(),
+            None => return,
+        };
+
+        self.remove_row(name);
+
+        let name = name.to_string();
+        glib::MainContext::default().spawn_local(async move {
+            context.stream_restore().delete(&[&name]).await;
+        });
+    }
+}
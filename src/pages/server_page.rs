@@ -0,0 +1,165 @@
+use gtk::prelude::*;
+
+use adw::subclass::prelude::*;
+use gtk::subclass::prelude::*;
+
+use std::cell::RefCell;
+use std::future::Future;
+
+use futures::future::AbortHandle;
+
+use gtk::{subclass::prelude::ObjectSubclassIsExt, CompositeTemplate};
+use once_cell::sync::Lazy;
+use once_cell::unsync::OnceCell;
+
+use crate::pages::{ConfigurationPage, OutputPage, PlaybackPage, RestorePage};
+
+mod imp {
+    use super::*;
+
+    /// Everything belonging to one server connection: its context and its pages.
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(file = "server_page.ui")]
+    pub struct ServerPage {
+        #[template_child]
+        pub stack: TemplateChild<adw::ViewStack>,
+        #[template_child]
+        pub playback_page: TemplateChild<PlaybackPage>,
+        #[template_child]
+        pub output_page: TemplateChild<OutputPage>,
+        #[template_child]
+        pub restore_page: TemplateChild<RestorePage>,
+        #[template_child]
+        pub configuration_page: TemplateChild<ConfigurationPage>,
+
+        pub context: OnceCell<pulse_async::Context>,
+        pub server: RefCell<String>,
+        /// Tasks keeping the page in sync, stopped on disconnect.
+        pub tasks: RefCell<Vec<AbortHandle>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ServerPage {
+        const NAME: &'static str = "ServerPage";
+        type Type = super::ServerPage;
+        type ParentType = adw::Bin;
+        type Interfaces = (gtk::Buildable,);
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ServerPage {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecString::new(
+                    "server",
+                    "Server",
+                    "Name of the server, or the connection state",
+                    Some(""),
+                    glib::ParamFlags::READWRITE | glib::ParamFlags::EXPLICIT_NOTIFY,
+                )]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(
+            &self,
+            obj: &Self::Type,
+            _id: usize,
+            value: &glib::Value,
+            pspec: &glib::ParamSpec,
+        ) {
+            match pspec.name() {
+                "server" => obj.set_server(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _obj: &Self::Type, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "server" => self.server.borrow().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+    impl WidgetImpl for ServerPage {}
+    impl BuildableImpl for ServerPage {}
+    impl BinImpl for ServerPage {}
+}
+
+glib::wrapper! {
+    pub struct ServerPage(ObjectSubclass<imp::ServerPage>) @extends gtk::Widget;
+}
+
+impl ServerPage {
+    pub fn new() -> Self {
+        glib::Object::new(&[]).expect("Failed to create ServerPage")
+    }
+
+    pub fn init_context(&self, context: pulse_async::Context) {
+        self.imp().restore_page.init_context(context.clone());
+        self.imp().configuration_page.init_context(context.clone());
+        self.imp().context.set(context).unwrap()
+    }
+
+    pub fn context(&self) -> &pulse_async::Context {
+        self.imp().context.get().unwrap()
+    }
+
+    /// Runs `task` on the main context until the page gets disconnected.
+    pub fn spawn<F: Future<Output = ()> + 'static>(&self, task: F) {
+        let (task, handle) = futures::future::abortable(task);
+        self.imp().tasks.borrow_mut().push(handle);
+
+        glib::MainContext::default().spawn_local(async move {
+            task.await.ok();
+        });
+    }
+
+    /// Stops the tasks of this page and disconnects from its server.
+    pub fn disconnect(&self) {
+        for task in self.imp().tasks.take() {
+            task.abort();
+        }
+
+        if let Some(context) = self.imp().context.get() {
+            context.clone().disconnect();
+        }
+    }
+
+    pub fn stack(&self) -> &adw::ViewStack {
+        &self.imp().stack
+    }
+
+    pub fn playback_page(&self) -> &PlaybackPage {
+        &self.imp().playback_page
+    }
+
+    pub fn output_page(&self) -> &OutputPage {
+        &self.imp().output_page
+    }
+
+    pub fn restore_page(&self) -> &RestorePage {
+        &self.imp().restore_page
+    }
+
+    pub fn configuration_page(&self) -> &ConfigurationPage {
+        &self.imp().configuration_page
+    }
+
+    pub fn server(&self) -> String {
+        self.imp().server.borrow().clone()
+    }
+
+    pub fn set_server(&self, server: &str) {
+        *self.imp().server.borrow_mut() = server.to_string();
+        self.notify("server");
+    }
+}
@@ -0,0 +1,105 @@
+//! Pages driven by [`FakeBackend`] instead of a sound server.
+//!
+//! GTK may only be used from the thread that initialized it, so everything runs in one test.
+
+use futures::executor::block_on;
+use pulse::context::subscribe::Operation;
+use pulse_async::backend::{
+    fake::{self, FakeBackend},
+    Backend,
+};
+
+use super::{OutputPage, PlaybackPage};
+
+fn output_page(backend: &FakeBackend) {
+    let page = OutputPage::new();
+
+    backend.add_sink(fake::sink(0, "speakers"));
+    block_on(page.event(backend, &Operation::New, 0));
+    assert_eq!(page.playback_items().len(), 1);
+
+    backend.update_sink(0, |info| info.mute = true);
+    block_on(page.event(backend, &Operation::Changed, 0));
+    assert_eq!(page.playback_items().len(), 1);
+
+    // Unknown sinks are ignored, and only `New` creates items
+    block_on(page.event(backend, &Operation::Changed, 7));
+    assert_eq!(page.playback_items().len(), 1);
+
+    backend.add_sink(fake::sink(5, "early"));
+    block_on(page.event(backend, &Operation::Changed, 5));
+    assert_eq!(page.playback_items().len(), 1);
+    backend.remove_sink(5);
+
+    backend.remove_sink(0);
+    block_on(page.event(backend, &Operation::Removed, 0));
+    assert!(page.playback_items().is_empty());
+
+    // A profile change shows up as the card's devices
+    let card = backend.add_card("card");
+    let mut sink = fake::sink(1, "card-sink");
+    sink.card = Some(card);
+    backend.add_sink(sink);
+    block_on(page.event(backend, &Operation::New, 1));
+
+    // Card events may arrive before the `New` of its devices
+    let mut sink = fake::sink(2, "card-sink-2");
+    sink.card = Some(card);
+    backend.add_sink(sink);
+
+    backend.update_sink(1, |info| info.mute = true);
+    block_on(page.card_changed(backend, card));
+    assert_eq!(
+        page.playback_items().keys().copied().collect::<Vec<_>>(),
+        [1]
+    );
+}
+
+fn playback_page(backend: &FakeBackend) {
+    let page = PlaybackPage::new();
+
+    backend.add_sink_input(fake::sink_input(3, 1, "Player"));
+    block_on(page.event(backend, &Operation::New, 3));
+    assert_eq!(page.playback_items().len(), 1);
+
+    // Client events only refresh streams already shown
+    let mut input = fake::sink_input(6, 1, "Other");
+    input.client = Some(0);
+    backend.add_sink_input(input);
+    block_on(page.client_changed(backend, 0));
+    assert_eq!(page.playback_items().len(), 1);
+    backend.remove_sink_input(6);
+
+    backend.remove_sink_input(3);
+    block_on(page.event(backend, &Operation::Removed, 3));
+    assert!(page.playback_items().is_empty());
+
+    // Recording streams are only updated once the caller chose to show them
+    backend.add_source_output(fake::source_output(4, 0, "Recorder"));
+    block_on(page.input_event(backend, &Operation::Changed, 4));
+    assert!(page.input_items().is_empty());
+
+    let output = block_on(backend.source_output(4)).unwrap();
+    page.add_input_item(&output);
+
+    backend.update_source_output(4, |info| info.mute = true);
+    block_on(page.input_event(backend, &Operation::Changed, 4));
+    assert_eq!(page.input_items().len(), 1);
+
+    backend.remove_source_output(4);
+    block_on(page.input_event(backend, &Operation::Removed, 4));
+    assert!(page.input_items().is_empty());
+}
+
+#[test]
+fn events() {
+    // Needs a display
+    if gtk::init().is_err() {
+        return;
+    }
+    adw::init();
+
+    let backend = FakeBackend::new();
+    output_page(&backend);
+    playback_page(&backend);
+}
@@ -3,47 +3,77 @@ use std::rc::Rc;
 use std::time::Duration;
 
 use ::pulse::context::FlagSet;
-use ::pulse::proplist::Proplist;
 use adw::prelude::*;
-use futures::channel::mpsc::UnboundedSender;
 use futures::StreamExt;
+use glib::clone;
+use pulse::channelmap::Position;
 use pulse::context::subscribe::Facility;
 use pulse::context::subscribe::InterestMaskSet;
 use pulse::context::subscribe::Operation;
-use pulse_async::SinkInputInfo;
+use pulse::proplist::Proplist;
+use pulse_async::dsp::Signal;
+use pulse_async::{
+    AppMetadata, FadeCurve, ProplistBuilder, SinkInfo, SinkInputInfo, SourceInfo, SourceOutputInfo,
+    VolumeController, VolumeLevel, VolumeTarget,
+};
 
+use crate::pages::ServerPage;
+use crate::widgets::{ChannelScale, SinkItem, SourceItem};
 use crate::window::CathodeWindow;
+use crate::CathodeApplication;
 
-struct VolumeUpdateEvent {
-    id: u32,
-    volume: f64,
-    target: Target,
-    done_notify: Box<dyn FnOnce()>,
-}
+const APP_ID: &str = "com.github.polymelex.cathode";
 
-enum Target {
-    SinkVolume,
-    SinkInputVolume,
-}
+/// How long the "Fade Out" button of a stream takes.
+const FADE_OUT: Duration = Duration::from_secs(3);
+
+/// How long to wait for the server before giving up on a request, so a stalled server can't
+/// freeze the pages.
+const OPERATION_TIMEOUT: Duration = Duration::from_secs(10);
 
-pub fn run(win: CathodeWindow) {
-    glib::MainContext::default().spawn_local(async move {
-        let mut proplist = Proplist::new().unwrap();
-        proplist
-            .set_str(
-                pulse::proplist::properties::APPLICATION_ID,
-                "com.github.polymelex.cathode",
-            )
-            .unwrap();
+/// How long server events are gathered before updating the pages, so a burst of changes (e.g.
+/// someone dragging a volume slider) costs one lookup per object.
+const EVENT_WINDOW: Duration = Duration::from_millis(100);
 
-        let context = pulse_async::context::Context::new_with_proplist("Cathode", &proplist);
-        win.init_context(context.clone());
+/// Connects `page` to `server` and keeps it in sync with it.
+pub fn run(win: CathodeWindow, page: ServerPage, server: Option<String>) {
+    let proplist = ProplistBuilder::new()
+        .application_id(APP_ID)
+        .build()
+        .unwrap();
 
-        let win = win.clone();
+    let context = pulse_async::context::Context::new_with_proplist("Cathode", &proplist);
+    context.set_operation_timeout(Some(OPERATION_TIMEOUT));
+    page.init_context(context.clone());
 
-        context.connect(None, FlagSet::NOFAIL).await.unwrap();
+    if let Some(app) = win
+        .application()
+        .and_then(|app| app.downcast::<CathodeApplication>().ok())
+    {
+        let output_page = page.output_page();
+        output_page.set_show_monitors(app.show_monitors());
+        output_page.connect_show_monitors_changed(clone!(@weak app => move |show| {
+            app.set_show_monitors(show);
+        }));
+    }
+
+    let runner = page.clone();
+    runner.spawn(async move {
+        page.set_server(server.as_deref().unwrap_or("Connecting…"));
 
-        let (tx, mut rx) = futures::channel::mpsc::unbounded::<VolumeUpdateEvent>();
+        let flags = if server.is_some() {
+            // An explicitly requested server should fail loudly instead of waiting for a local one
+            FlagSet::NOAUTOSPAWN
+        } else {
+            FlagSet::NOFAIL
+        };
+
+        if context.connect(server.as_deref(), flags).await.is_err() {
+            page.set_server("Connection failed");
+            return;
+        }
+
+        page.set_server(&context.server().unwrap_or_default());
 
         let sink_input_list = context
             .introspect()
@@ -51,8 +81,11 @@ pub fn run(win: CathodeWindow) {
             .await
             .unwrap_or_else(|_| Vec::new());
 
-        for input in sink_input_list {
-            new_sink_input(&win, &tx, &input);
+        for input in sink_input_list
+            .iter()
+            .filter(|input| !is_own(&input.proplist))
+        {
+            new_sink_input(&page, input);
         }
 
         let sink_list = context
@@ -62,108 +95,309 @@ pub fn run(win: CathodeWindow) {
             .unwrap_or_else(|_| Vec::new());
 
         for output in sink_list {
-            let id = output.index;
-            let item = win.output_page().add_item(&output);
-
-            let tx = tx.clone();
-            item.connect_volume_changed(move |scale, done_notify| {
-                let volume = scale.value();
-
-                tx.unbounded_send(VolumeUpdateEvent {
-                    id,
-                    volume,
-                    done_notify,
-                    target: Target::SinkVolume,
-                })
-                .ok();
-            });
-
-            item.level_box().init_stream(context.clone(), id, None);
+            new_sink(&page, &output);
         }
 
-        glib::MainContext::default().spawn_local(subscribe(win.clone(), tx.clone()));
+        let source_list = context
+            .introspect()
+            .source_list()
+            .await
+            .unwrap_or_else(|_| Vec::new());
 
-        while let Some(event) = rx.next().await {
-            let mut introspect = context.introspect();
-            let id = event.id;
-            let volume = event.volume;
+        for source in source_list {
+            new_source(&page, &source);
+        }
 
-            let _success = match event.target {
-                Target::SinkVolume => introspect.set_sink_volume(id, volume).await,
-                Target::SinkInputVolume => introspect.set_sink_input_volume(id, volume).await,
-            };
+        let source_output_list = context
+            .introspect()
+            .source_output_list()
+            .await
+            .unwrap_or_else(|_| Vec::new());
 
-            glib::timeout_add_local_once(Duration::from_millis(100), move || {
-                (event.done_notify)();
-            });
+        for output in source_output_list
+            .iter()
+            .filter(|output| !is_own(&output.proplist))
+        {
+            new_source_output(&page, output);
         }
+
+        update_defaults(&page).await;
+
+        page.spawn(stream_restore(page.clone()));
+        page.spawn(device_manager(page.clone()));
+
+        subscribe(page).await;
     });
 }
 
-async fn subscribe(win: CathodeWindow, tx: UnboundedSender<VolumeUpdateEvent>) {
-    let context = win.context();
-    let mut sub = context.subscribe(InterestMaskSet::SINK_INPUT | InterestMaskSet::SINK);
+/// Keeps the list of remembered streams up to date.
+async fn stream_restore(page: ServerPage) {
+    let context = page.context();
+    let mut events = context.subscribe_stream_restore();
 
-    let playback_page = win.playback_page().clone();
-    let output_page = win.output_page().clone();
+    loop {
+        if let Ok(entries) = context.stream_restore().read().await {
+            page.restore_page().set_entries(&entries);
+        }
+
+        if events.next().await.is_none() {
+            break;
+        }
+    }
+}
+
+/// Keeps the list of preferred devices up to date.
+async fn device_manager(page: ServerPage) {
+    let context = page.context();
+    let mut events = context.subscribe_device_manager();
+
+    loop {
+        match context.device_manager().read().await {
+            Ok(devices) => page.configuration_page().set_devices(&devices),
+            Err(_) => page.configuration_page().set_unavailable(),
+        }
+
+        if events.next().await.is_none() {
+            break;
+        }
+    }
+}
+
+/// Sends the values of `scale` through `controller`, keeping server updates from fighting the
+/// user while a change is in flight.
+fn connect_volume(scale: &ChannelScale, controller: VolumeController) {
+    controller.connect_settled(clone!(@weak scale => move |_| {
+        scale.set_busy(false);
+    }));
+
+    scale.connect_volume_changed(clone!(@weak scale => move |gtk_scale| {
+        scale.set_busy(true);
+
+        let set = controller.set(gtk_scale.value());
+        glib::MainContext::default().spawn_local(async move {
+            set.await;
+        });
+    }));
+}
+
+/// Whether a stream is one Cathode created itself, for level meters, test tones or latency
+/// measurements.
+fn is_own(proplist: &Proplist) -> bool {
+    AppMetadata::from(proplist).id.as_deref() == Some(APP_ID)
+}
+
+/// Shows which sink and source are the default ones.
+async fn update_defaults(page: &ServerPage) {
+    if let Ok(info) = page.context().introspect().server_info().await {
+        page.output_page().set_defaults(
+            info.default_sink_name.as_deref(),
+            info.default_source_name.as_deref(),
+        );
+    }
+}
+
+async fn subscribe(page: ServerPage) {
+    let context = page.context();
+    let mut sub = context.subscribe_debounced(
+        InterestMaskSet::SINK_INPUT
+            | InterestMaskSet::SINK
+            | InterestMaskSet::SOURCE
+            | InterestMaskSet::SOURCE_OUTPUT
+            | InterestMaskSet::CARD
+            | InterestMaskSet::SERVER
+            | InterestMaskSet::CLIENT,
+        EVENT_WINDOW,
+    );
+
+    let playback_page = page.playback_page().clone();
+    let output_page = page.output_page().clone();
 
     while let Some(event) = sub.next().await {
-        if let Ok((facility, operation, id)) = event {
-            match facility {
-                Some(Facility::SinkInput) => {
-                    if let Some(ref op) = operation {
-                        match op {
-                            Operation::New => {
-                                if let Ok(info) = context.introspect().sink_input(id).await {
-                                    playback_page.add_item(&info);
-                                    new_sink_input(&win, &tx, &info);
-                                }
-                            }
-                            Operation::Changed => {
-                                if let Ok(info) = context.introspect().sink_input(id).await {
-                                    playback_page.add_item(&info);
-                                }
-                            }
-                            Operation::Removed => {
-                                playback_page.remove_item(id);
-                            }
+        let (facility, op, id) = match event {
+            Ok((Some(facility), Some(op), id)) => (facility, op, id),
+            _ => continue,
+        };
+
+        match facility {
+            Facility::SinkInput => match op {
+                Operation::New => {
+                    if let Ok(info) = context.introspect().sink_input(id).await {
+                        if !is_own(&info.proplist) {
+                            new_sink_input(&page, &info);
                         }
                     }
                 }
-                Some(Facility::Sink) => {
-                    if let Some(ref op) = operation {
-                        output_page.event(&context, op, id).await;
+                _ => playback_page.event(context, &op, id).await,
+            },
+            Facility::Sink => match op {
+                Operation::New => {
+                    if let Ok(info) = context.introspect().sink(id).await {
+                        new_sink(&page, &info);
+                    }
+                }
+                _ => output_page.event(context, &op, id).await,
+            },
+            Facility::Source => match op {
+                Operation::New => {
+                    if let Ok(info) = context.introspect().source(id).await {
+                        new_source(&page, &info);
                     }
                 }
-                _ => {}
-            };
+                _ => output_page.source_event(context, &op, id).await,
+            },
+            Facility::SourceOutput => match op {
+                Operation::New => {
+                    if let Ok(info) = context.introspect().source_output(id).await {
+                        if !is_own(&info.proplist) {
+                            new_source_output(&page, &info);
+                        }
+                    }
+                }
+                _ => playback_page.input_event(context, &op, id).await,
+            },
+            Facility::Card => {
+                if op != Operation::Removed {
+                    output_page.card_changed(context, id).await;
+                }
+            }
+            Facility::Server => {
+                update_defaults(&page).await;
+            }
+            Facility::Client => {
+                if op == Operation::Changed {
+                    playback_page.client_changed(context, id).await;
+                }
+            }
+            _ => {}
         }
     }
 }
 
-fn new_sink_input(
-    win: &CathodeWindow,
-    tx: &UnboundedSender<VolumeUpdateEvent>,
-    input: &SinkInputInfo,
-) {
-    let id = input.index;
-    let item = win.playback_page().add_item(&input);
+async fn test_output(context: pulse_async::Context, item: SinkItem, id: u32, signal: Signal) {
+    let info = match context.introspect().sink(id).await {
+        Ok(info) => info,
+        Err(_) => return,
+    };
 
-    let tx = tx.clone();
-    item.connect_volume_changed(move |scale, done_notify| {
-        let volume = scale.value();
+    item.set_test_channel(Some(""));
 
-        tx.unbounded_send(VolumeUpdateEvent {
+    context
+        .play_test_signal(
             id,
-            volume,
-            done_notify,
-            target: Target::SinkInputVolume,
-        })
+            &info.channel_map,
+            signal,
+            Duration::from_secs(2),
+            |_, position| {
+                let name = Position::to_pretty_string(position).unwrap_or_default();
+                item.set_test_channel(Some(&name));
+            },
+        )
+        .await
         .ok();
+
+    item.set_test_channel(None);
+}
+
+fn new_sink(page: &ServerPage, output: &SinkInfo) {
+    let id = output.index;
+    let context = page.context().clone();
+    let item = page.output_page().add_item(output);
+
+    let controller = VolumeController::new(context.clone(), VolumeTarget::Sink(id));
+    connect_volume(item.channel_scale(), controller);
+
+    item.level_box()
+        .init_stream(context.clone(), output.monitor_source, None);
+    item.init_spectrum(context.clone(), output.monitor_source, None);
+
+    item.connect_test(clone!(@weak item => move |signal| {
+        let test = test_output(context.clone(), item, id, signal);
+        glib::MainContext::default().spawn_local(test);
+    }));
+}
+
+fn new_sink_input(page: &ServerPage, input: &SinkInputInfo) {
+    let id = input.index;
+    let item = page.playback_page().add_item(&input);
+
+    let controller = VolumeController::new(page.context().clone(), VolumeTarget::SinkInput(id));
+    connect_volume(item.channel_scale(), controller);
+
+    let context = page.context().clone();
+    item.connect_fade_out(move || {
+        let context = context.clone();
+        glib::MainContext::default().spawn_local(async move {
+            context
+                .fade_sink_input_volume(id, VolumeLevel::MUTED, FADE_OUT, FadeCurve::Logarithmic)
+                .await
+                .ok();
+        });
     });
 
     item.level_box()
-        .init_stream(win.context().clone(), input.sink, Some(id));
+        .init_stream(page.context().clone(), input.sink, Some(id));
+    item.init_spectrum(page.context().clone(), input.sink, Some(id));
+}
+
+fn new_source(page: &ServerPage, source: &SourceInfo) {
+    let id = source.index;
+    let item = page.output_page().add_source_item(source);
+
+    let controller = VolumeController::new(page.context().clone(), VolumeTarget::Source(id));
+    connect_volume(item.channel_scale(), controller);
+
+    let context = page.context().clone();
+    item.connect_mute_toggled(clone!(@weak item => move |mute| {
+        let context = context.clone();
+        glib::MainContext::default().spawn_local(clone!(@weak item => async move {
+            if !context.introspect().set_source_mute(id, mute).await {
+                revert_source(&context, &item, id).await;
+            }
+        }));
+    }));
+
+    let context = page.context().clone();
+    item.connect_port_selected(clone!(@weak item => move |port| {
+        let context = context.clone();
+        let port = port.to_string();
+        glib::MainContext::default().spawn_local(clone!(@weak item => async move {
+            if !context.introspect().set_source_port(id, &port).await {
+                revert_source(&context, &item, id).await;
+            }
+        }));
+    }));
+
+    item.level_box()
+        .init_stream(page.context().clone(), id, None);
+}
+
+/// Puts the widgets of a source back to the server state after a failed change.
+async fn revert_source(context: &pulse_async::Context, item: &SourceItem, id: u32) {
+    if let Ok(info) = context.introspect().source(id).await {
+        item.update(&info);
+    }
+}
+
+fn new_source_output(page: &ServerPage, output: &SourceOutputInfo) {
+    let id = output.index;
+    let item = page.playback_page().add_input_item(output);
+
+    let controller = VolumeController::new(page.context().clone(), VolumeTarget::SourceOutput(id));
+    connect_volume(item.channel_scale(), controller);
+
+    let context = page.context().clone();
+    item.connect_mute_toggled(clone!(@weak item => move |mute| {
+        let context = context.clone();
+        glib::MainContext::default().spawn_local(clone!(@weak item => async move {
+            if !context.introspect().set_source_output_mute(id, mute).await {
+                if let Ok(info) = context.introspect().source_output(id).await {
+                    item.update(&info);
+                }
+            }
+        }));
+    }));
+
+    item.init_meter(page.context().clone(), output.source);
 }
 
 pub struct StreamGuard {
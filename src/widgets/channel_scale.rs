@@ -3,7 +3,7 @@ use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 
 use gtk::{traits::RangeExt, CompositeTemplate};
-use std::{cell::Cell, rc::Rc};
+use std::cell::Cell;
 
 mod imp {
     use super::*;
@@ -13,6 +13,11 @@ mod imp {
     pub struct ChannelScale {
         #[template_child]
         pub scale: TemplateChild<gtk::Scale>,
+
+        /// Set while the user's value has not reached the server yet.
+        pub busy: Cell<bool>,
+        /// Set while the value is being updated from server state.
+        pub updating: Cell<bool>,
     }
 
     #[glib::object_subclass]
@@ -62,21 +67,36 @@ impl ChannelScale {
         &self.imp().scale
     }
 
+    /// Shows `volume` from the server, unless the user's own change is still in flight.
+    pub fn set_volume(&self, volume: f64) {
+        let imp = self.imp();
+
+        if imp.busy.get() {
+            return;
+        }
+
+        imp.updating.set(true);
+        imp.scale.set_value(volume);
+        imp.updating.set(false);
+    }
+
+    pub fn set_busy(&self, busy: bool) {
+        self.imp().busy.set(busy);
+    }
+
+    /// Calls `cb` when the user moves the scale.
     pub fn connect_volume_changed<F>(&self, cb: F)
     where
-        F: Fn(&gtk::Scale, Box<dyn FnOnce()>) + 'static,
+        F: Fn(&gtk::Scale) + 'static,
     {
-        let acked = Rc::new(Cell::new(true));
+        let this = self.downgrade();
         self.imp().scale.connect_value_changed(move |scale| {
-            if acked.get() {
-                acked.set(false);
-
-                let acked = acked.clone();
-                let done_notify = move || {
-                    acked.set(true);
-                };
+            let updating = this
+                .upgrade()
+                .map_or(false, |this| this.imp().updating.get());
 
-                cb(scale, Box::new(done_notify))
+            if !updating {
+                cb(scale)
             }
         });
     }
@@ -0,0 +1,123 @@
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use std::time::Duration;
+
+/// How often the memory statistics are refreshed.
+const REFRESH: Duration = Duration::from_secs(2);
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(file = "diagnostics_dialog.ui")]
+    pub struct DiagnosticsDialog {
+        #[template_child]
+        pub version_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub host_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub sample_spec_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub memblock_total_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub memblock_allocated_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub scache_label: TemplateChild<gtk::Label>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for DiagnosticsDialog {
+        const NAME: &'static str = "DiagnosticsDialog";
+        type Type = super::DiagnosticsDialog;
+        type ParentType = gtk::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for DiagnosticsDialog {}
+    impl WidgetImpl for DiagnosticsDialog {}
+    impl WindowImpl for DiagnosticsDialog {}
+}
+
+glib::wrapper! {
+    pub struct DiagnosticsDialog(ObjectSubclass<imp::DiagnosticsDialog>)
+        @extends gtk::Widget, gtk::Window,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+fn format_blocks(count: u32, size: u32) -> String {
+    format!("{} ({})", count, glib::format_size(size as u64))
+}
+
+impl DiagnosticsDialog {
+    pub fn new<P: glib::IsA<gtk::Window>>(parent: &P, context: pulse_async::Context) -> Self {
+        let dialog: Self = glib::Object::new(&[("transient-for", parent)])
+            .expect("Failed to create DiagnosticsDialog");
+
+        let weak = dialog.downgrade();
+        glib::MainContext::default().spawn_local(async move {
+            if let Ok(info) = context.introspect().server_info().await {
+                match weak.upgrade() {
+                    Some(dialog) => dialog.set_server_info(&info),
+                    None => return,
+                }
+            }
+
+            // Keep polling until the dialog is closed
+            loop {
+                let stat = context.introspect().stat().await;
+
+                match weak.upgrade() {
+                    Some(dialog) => {
+                        if let Ok(stat) = stat {
+                            dialog.set_stat(&stat);
+                        }
+                    }
+                    None => break,
+                }
+
+                context.sleep(REFRESH).await;
+            }
+        });
+
+        dialog
+    }
+
+    fn set_server_info(&self, info: &pulse_async::ServerInfo) {
+        let imp = self.imp();
+
+        imp.version_label.set_label(&format!(
+            "{} {}",
+            info.server_name.as_deref().unwrap_or_default(),
+            info.server_version.as_deref().unwrap_or_default()
+        ));
+        imp.host_label.set_label(&format!(
+            "{}@{}",
+            info.user_name.as_deref().unwrap_or_default(),
+            info.host_name.as_deref().unwrap_or_default()
+        ));
+        imp.sample_spec_label.set_label(&info.sample_spec.print());
+    }
+
+    fn set_stat(&self, stat: &pulse_async::StatInfo) {
+        let imp = self.imp();
+
+        imp.memblock_total_label.set_label(&format_blocks(
+            stat.memblock_total,
+            stat.memblock_total_size,
+        ));
+        imp.memblock_allocated_label.set_label(&format_blocks(
+            stat.memblock_allocated,
+            stat.memblock_allocated_size,
+        ));
+        imp.scache_label
+            .set_label(&glib::format_size(stat.scache_size as u64));
+    }
+}
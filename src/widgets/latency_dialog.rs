@@ -0,0 +1,161 @@
+use glib::clone;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::unsync::OnceCell;
+use pulse::time::MicroSeconds;
+use std::cell::RefCell;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(file = "latency_dialog.ui")]
+    pub struct LatencyDialog {
+        #[template_child]
+        pub sink_dropdown: TemplateChild<gtk::DropDown>,
+        #[template_child]
+        pub source_dropdown: TemplateChild<gtk::DropDown>,
+        #[template_child]
+        pub round_trip_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub sink_latency_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub configured_latency_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub measure_button: TemplateChild<gtk::Button>,
+
+        pub context: OnceCell<pulse_async::Context>,
+        pub sinks: RefCell<Vec<u32>>,
+        pub sources: RefCell<Vec<u32>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for LatencyDialog {
+        const NAME: &'static str = "LatencyDialog";
+        type Type = super::LatencyDialog;
+        type ParentType = gtk::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for LatencyDialog {
+        fn constructed(&self, obj: &Self::Type) {
+            self.parent_constructed(obj);
+
+            self.measure_button
+                .connect_clicked(clone!(@weak obj => move |_| obj.measure()));
+        }
+    }
+
+    impl WidgetImpl for LatencyDialog {}
+    impl WindowImpl for LatencyDialog {}
+}
+
+glib::wrapper! {
+    pub struct LatencyDialog(ObjectSubclass<imp::LatencyDialog>)
+        @extends gtk::Widget, gtk::Window,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+fn format_latency(latency: MicroSeconds) -> String {
+    format!("{:.1} ms", latency.0 as f64 / 1000.0)
+}
+
+impl LatencyDialog {
+    pub fn new<P: glib::IsA<gtk::Window>>(parent: &P, context: pulse_async::Context) -> Self {
+        let dialog: Self = glib::Object::new(&[("transient-for", parent)])
+            .expect("Failed to create LatencyDialog");
+
+        dialog.imp().context.set(context).unwrap();
+
+        let this = dialog.clone();
+        glib::MainContext::default().spawn_local(async move {
+            this.populate().await;
+        });
+
+        dialog
+    }
+
+    fn context(&self) -> &pulse_async::Context {
+        self.imp().context.get().unwrap()
+    }
+
+    async fn populate(&self) {
+        let imp = self.imp();
+        let introspect = self.context().introspect();
+
+        let sinks = introspect.sink_list().await.unwrap_or_default();
+        let sources = introspect.source_list().await.unwrap_or_default();
+
+        let names: Vec<String> = sinks
+            .iter()
+            .map(|sink| sink.description.clone().unwrap_or_default())
+            .collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        imp.sink_dropdown
+            .set_model(Some(&gtk::StringList::new(&names)));
+        *imp.sinks.borrow_mut() = sinks.iter().map(|sink| sink.index).collect();
+
+        let names: Vec<String> = sources
+            .iter()
+            .map(|source| source.description.clone().unwrap_or_default())
+            .collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        imp.source_dropdown
+            .set_model(Some(&gtk::StringList::new(&names)));
+        *imp.sources.borrow_mut() = sources.iter().map(|source| source.index).collect();
+    }
+
+    fn measure(&self) {
+        let imp = self.imp();
+
+        let sink = imp
+            .sinks
+            .borrow()
+            .get(imp.sink_dropdown.selected() as usize)
+            .copied();
+        let source = imp
+            .sources
+            .borrow()
+            .get(imp.source_dropdown.selected() as usize)
+            .copied();
+
+        let (sink, source) = match (sink, source) {
+            (Some(sink), Some(source)) => (sink, source),
+            _ => return,
+        };
+
+        imp.measure_button.set_sensitive(false);
+        imp.round_trip_label.set_label("…");
+
+        let dialog = self.clone();
+        glib::MainContext::default().spawn_local(async move {
+            let context = dialog.context().clone();
+            let imp = dialog.imp();
+
+            match context.measure_latency(sink, source).await {
+                Ok(measurement) => imp.round_trip_label.set_label(&format!(
+                    "{:.1} ms",
+                    measurement.round_trip.as_secs_f64() * 1000.0
+                )),
+                Err(_) => imp.round_trip_label.set_label("Not detected"),
+            }
+
+            if let Ok(info) = context.introspect().sink(sink).await {
+                imp.sink_latency_label
+                    .set_label(&format_latency(info.latency));
+                imp.configured_latency_label
+                    .set_label(&format_latency(info.configured_latency));
+            }
+
+            imp.measure_button.set_sensitive(true);
+        });
+    }
+}
@@ -8,7 +8,9 @@ use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
 use once_cell::sync::Lazy;
 use once_cell::unsync::OnceCell;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+
+use pulse_async::dsp::{MeterConfig, METER_FLOOR_DB};
 
 mod imp {
     use super::*;
@@ -17,11 +19,16 @@ mod imp {
     #[template(file = "level_box.ui")]
     pub struct LevelBox {
         pub icon_name: RefCell<String>,
+        pub db_scale: Cell<bool>,
 
         #[template_child]
         pub level_bar: TemplateChild<gtk::LevelBar>,
         #[template_child]
+        pub hold_marker: TemplateChild<gtk::Box>,
+        #[template_child]
         pub image: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub clip_button: TemplateChild<gtk::Button>,
 
         pub stream: RefCell<OnceCell<SourceId>>,
     }
@@ -46,19 +53,35 @@ mod imp {
     }
 
     impl ObjectImpl for LevelBox {
-        fn constructed(&self, _obj: &Self::Type) {}
+        fn constructed(&self, obj: &Self::Type) {
+            self.parent_constructed(obj);
+
+            // Clip indicator stays lit until acknowledged
+            self.clip_button.connect_clicked(|button| {
+                button.set_visible(false);
+            });
+        }
 
         fn properties() -> &'static [glib::ParamSpec] {
             static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
-                vec![glib::ParamSpecString::new(
-                    "icon-name",
-                    "App Icon",
-                    "The app icon",
-                    Some("multimedia-player-symbolic"),
-                    glib::ParamFlags::READWRITE
-                        | glib::ParamFlags::CONSTRUCT
-                        | glib::ParamFlags::EXPLICIT_NOTIFY,
-                )]
+                vec![
+                    glib::ParamSpecString::new(
+                        "icon-name",
+                        "App Icon",
+                        "The app icon",
+                        Some("multimedia-player-symbolic"),
+                        glib::ParamFlags::READWRITE
+                            | glib::ParamFlags::CONSTRUCT
+                            | glib::ParamFlags::EXPLICIT_NOTIFY,
+                    ),
+                    glib::ParamSpecBoolean::new(
+                        "db-scale",
+                        "dB Scale",
+                        "Show RMS/peak in dBFS with a clip indicator",
+                        false,
+                        glib::ParamFlags::READWRITE | glib::ParamFlags::EXPLICIT_NOTIFY,
+                    ),
+                ]
             });
 
             PROPERTIES.as_ref()
@@ -73,6 +96,7 @@ mod imp {
         ) {
             match pspec.name() {
                 "icon-name" => obj.set_icon(value.get().unwrap()),
+                "db-scale" => obj.set_db_scale(value.get().unwrap()),
                 _ => unimplemented!(),
             }
         }
@@ -80,6 +104,7 @@ mod imp {
         fn property(&self, _obj: &Self::Type, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
             match pspec.name() {
                 "icon-name" => self.icon_name.borrow().to_value(),
+                "db-scale" => self.db_scale.get().to_value(),
                 _ => unimplemented!(),
             }
         }
@@ -115,12 +140,27 @@ impl LevelBox {
         self.notify("icon_name");
     }
 
+    pub fn set_db_scale(&self, db_scale: bool) {
+        self.imp().db_scale.set(db_scale);
+        self.notify("db-scale");
+    }
+
     pub fn level_bar(&self) -> &gtk::LevelBar {
         &self.imp().level_bar
     }
 
+    pub fn clip_button(&self) -> &gtk::Button {
+        &self.imp().clip_button
+    }
+
+    /// Replaces the stream feeding the bar, stopping the previous one.
     pub fn set_stream(&self, stream: SourceId) {
-        self.imp().stream.borrow_mut().set(stream).ok();
+        let mut current = self.imp().stream.borrow_mut();
+
+        if let Some(id) = current.take() {
+            id.remove();
+        }
+        current.set(stream).ok();
     }
 
     pub fn init_stream(
@@ -129,9 +169,14 @@ impl LevelBox {
         id: u32,
         stream_id: Option<u32>,
     ) {
+        if self.imp().db_scale.get() {
+            self.init_meter_stream(context, id, stream_id);
+            return;
+        }
+
         let level_bar = self.level_bar().clone();
         let source_id = glib::MainContext::default().spawn_local(async move {
-            let stream = context.crate_stream(id, stream_id);
+            let stream = context.crate_stream(id, stream_id, false);
 
             pin_mut!(stream);
 
@@ -155,4 +200,44 @@ impl LevelBox {
 
         self.set_stream(source_id);
     }
+
+    fn init_meter_stream(
+        &self,
+        context: pulse_async::context::Context,
+        id: u32,
+        stream_id: Option<u32>,
+    ) {
+        let level_bar = self.level_bar().clone();
+        let hold_marker = self.imp().hold_marker.get();
+        let clip_button = self.clip_button().clone();
+        let source_id = glib::MainContext::default().spawn_local(async move {
+            let stream = context.crate_meter_stream(id, stream_id, MeterConfig::default());
+
+            pin_mut!(stream);
+
+            while let Some(reading) = stream.next().await {
+                let max = level_bar.max_value();
+                let level = (reading.peak - METER_FLOOR_DB) / -METER_FLOOR_DB;
+
+                level_bar.set_value(level as f64 * max);
+
+                // Held peak as a line across the bar, at the height it reached
+                let hold = ((reading.peak_hold - METER_FLOOR_DB) / -METER_FLOOR_DB).clamp(0.0, 1.0);
+                let height = level_bar.height() - hold_marker.height();
+                hold_marker.set_margin_bottom((hold * height.max(0) as f32) as i32);
+                hold_marker.set_visible(hold > 0.0);
+
+                level_bar.set_tooltip_text(Some(&format!(
+                    "RMS {:.1} dBFS, peak {:.1} dBFS",
+                    reading.rms, reading.peak_hold
+                )));
+
+                if reading.clipped {
+                    clip_button.set_visible(true);
+                }
+            }
+        });
+
+        self.set_stream(source_id);
+    }
 }
@@ -1,11 +1,23 @@
 pub mod channel_scale;
 
+pub mod diagnostics_dialog;
+pub mod latency_dialog;
 pub mod level_box;
+pub mod server_dialog;
 pub mod sink_input_item;
 pub mod sink_item;
+pub mod source_item;
+pub mod source_output_item;
+pub mod spectrum_view;
 
 pub use channel_scale::ChannelScale;
 
+pub use diagnostics_dialog::DiagnosticsDialog;
+pub use latency_dialog::LatencyDialog;
 pub use level_box::LevelBox;
+pub use server_dialog::ServerDialog;
 pub use sink_input_item::SinkInputItem;
 pub use sink_item::SinkItem;
+pub use source_item::SourceItem;
+pub use source_output_item::SourceOutputItem;
+pub use spectrum_view::SpectrumView;
@@ -0,0 +1,100 @@
+use adw::prelude::*;
+use glib::clone;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use std::rc::Rc;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(file = "server_dialog.ui")]
+    pub struct ServerDialog {
+        #[template_child]
+        pub server_entry: TemplateChild<gtk::Entry>,
+        #[template_child]
+        pub recent_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub recent_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub connect_button: TemplateChild<gtk::Button>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ServerDialog {
+        const NAME: &'static str = "ServerDialog";
+        type Type = super::ServerDialog;
+        type ParentType = gtk::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ServerDialog {}
+    impl WidgetImpl for ServerDialog {}
+    impl WindowImpl for ServerDialog {}
+}
+
+glib::wrapper! {
+    pub struct ServerDialog(ObjectSubclass<imp::ServerDialog>)
+        @extends gtk::Widget, gtk::Window,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+impl ServerDialog {
+    pub fn new<P: glib::IsA<gtk::Window>>(parent: &P, recent: &[String]) -> Self {
+        let dialog: Self =
+            glib::Object::new(&[("transient-for", parent)]).expect("Failed to create ServerDialog");
+
+        let imp = dialog.imp();
+
+        for server in recent {
+            let row = adw::ActionRow::builder()
+                .title(server)
+                .activatable(true)
+                .build();
+            row.add_suffix(&gtk::Image::from_icon_name("go-next-symbolic"));
+
+            imp.recent_list.append(&row);
+        }
+
+        imp.recent_group.set_visible(!recent.is_empty());
+
+        dialog
+    }
+
+    /// Called with the chosen server, the dialog closes itself afterwards.
+    pub fn connect_server_selected<F>(&self, cb: F)
+    where
+        F: Fn(&str) + 'static,
+    {
+        let imp = self.imp();
+
+        let submit = Rc::new(clone!(@weak self as dialog => move |server: &str| {
+            let server = server.trim();
+
+            if !server.is_empty() {
+                cb(server);
+                dialog.close();
+            }
+        }));
+
+        // The entry activates the connect button, which is the default widget
+        let entry = imp.server_entry.get();
+        imp.connect_button
+            .connect_clicked(clone!(@weak entry, @strong submit => move |_| {
+                submit(entry.text().as_str());
+            }));
+
+        imp.recent_list.connect_row_activated(move |_, row| {
+            if let Some(row) = row.downcast_ref::<adw::ActionRow>() {
+                submit(row.title().as_str());
+            }
+        });
+    }
+}
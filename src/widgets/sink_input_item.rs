@@ -2,8 +2,7 @@ use adw::prelude::*;
 use adw::subclass::prelude::*;
 use gtk::subclass::prelude::*;
 
-use pulse::proplist::properties;
-use pulse_async::SinkInputInfo;
+use pulse_async::{AppMetadata, SinkInputInfo};
 
 use gtk::CompositeTemplate;
 use once_cell::sync::Lazy;
@@ -19,6 +18,12 @@ mod imp {
         pub channel_scale: TemplateChild<crate::widgets::ChannelScale>,
         #[template_child]
         pub level_box: TemplateChild<crate::widgets::LevelBox>,
+        #[template_child]
+        pub spectrum_popover: TemplateChild<gtk::Popover>,
+        #[template_child]
+        pub spectrum_view: TemplateChild<crate::widgets::SpectrumView>,
+        #[template_child]
+        pub fade_out_button: TemplateChild<gtk::Button>,
 
         pub title: RefCell<String>,
         pub subtitle: RefCell<String>,
@@ -117,9 +122,9 @@ impl SinkInputItem {
     }
 
     pub fn update(&self, info: &SinkInputInfo) {
-        let app_name = info.proplist.get_str(properties::APPLICATION_NAME);
+        let app = AppMetadata::from(&info.proplist);
 
-        if let Some(title) = app_name.as_deref() {
+        if let Some(title) = app.name.as_deref() {
             let title = glib::markup_escape_text(title);
             self.set_title(title.as_str());
         }
@@ -130,18 +135,10 @@ impl SinkInputItem {
         }
 
         let theme = gtk::IconTheme::default();
-        let icon_name = info
-            .proplist
-            .get_str(properties::APPLICATION_ICON_NAME)
-            .and_then(|icon| theme.has_icon(&icon).then(|| icon))
-            .or_else(|| {
-                let icon = info.proplist.get_str(properties::APPLICATION_ID)?;
-                theme.has_icon(&icon).then(|| icon)
-            })
-            .or_else(|| {
-                let icon = app_name?.to_lowercase();
-                theme.has_icon(&icon).then(|| icon)
-            });
+        let icon_name = app
+            .icon_candidates()
+            .into_iter()
+            .find(|icon| theme.has_icon(icon));
 
         if let Some(icon_name) = icon_name {
             self.set_icon(&icon_name);
@@ -168,6 +165,17 @@ impl SinkInputItem {
         &self.imp().level_box
     }
 
+    pub fn init_spectrum(
+        &self,
+        context: pulse_async::context::Context,
+        id: u32,
+        stream_id: Option<u32>,
+    ) {
+        let imp = self.imp();
+        imp.spectrum_view
+            .bind_popover(&imp.spectrum_popover, context, id, stream_id);
+    }
+
     pub fn set_title(&self, title: &str) {
         *self.imp().title.borrow_mut() = title.to_string();
         self.notify("title");
@@ -185,8 +193,15 @@ impl SinkInputItem {
 
     pub fn connect_volume_changed<F>(&self, cb: F)
     where
-        F: Fn(&gtk::Scale, Box<dyn FnOnce()>) + 'static,
+        F: Fn(&gtk::Scale) + 'static,
     {
         self.imp().channel_scale.get().connect_volume_changed(cb);
     }
+
+    pub fn connect_fade_out<F>(&self, cb: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().fade_out_button.connect_clicked(move |_| cb());
+    }
 }
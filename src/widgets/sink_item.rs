@@ -3,11 +3,11 @@ use adw::subclass::prelude::*;
 use gtk::subclass::prelude::*;
 
 use pulse::{def::SinkState, volume::Volume};
-use pulse_async::SinkInfo;
+use pulse_async::{dsp::Signal, SinkInfo};
 
 use gtk::CompositeTemplate;
 use once_cell::sync::Lazy;
-use std::{borrow::Borrow, cell::RefCell};
+use std::{borrow::Borrow, cell::RefCell, rc::Rc, time::Duration};
 
 mod imp {
     use super::*;
@@ -19,10 +19,26 @@ mod imp {
         pub channel_scale: TemplateChild<crate::widgets::ChannelScale>,
         #[template_child]
         pub level_box: TemplateChild<crate::widgets::LevelBox>,
+        #[template_child]
+        pub spectrum_popover: TemplateChild<gtk::Popover>,
+        #[template_child]
+        pub spectrum_view: TemplateChild<crate::widgets::SpectrumView>,
+        #[template_child]
+        pub test_sine_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub test_sweep_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub test_noise_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub test_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub default_image: TemplateChild<gtk::Image>,
 
         pub title: RefCell<String>,
         pub subtitle: RefCell<String>,
         pub icon_name: RefCell<String>,
+        /// Name of the sink, to tell whether it is the default one.
+        pub name: RefCell<Option<String>>,
     }
 
     #[glib::object_subclass]
@@ -117,12 +133,12 @@ impl SinkItem {
     }
 
     pub fn update(&self, info: &SinkInfo) {
+        // Virtual sinks have no ports
         let title = glib::markup_escape_text(
             info.active_port
                 .as_ref()
-                .unwrap()
-                .description
-                .as_deref()
+                .and_then(|port| port.description.as_deref())
+                .or(info.description.as_deref())
                 .unwrap_or(""),
         );
 
@@ -131,11 +147,12 @@ impl SinkItem {
         self.set_subtitle(subtitle.as_str());
 
         self.set_icon("audio-speakers-symbolic");
+        *self.imp().name.borrow_mut() = info.name.clone();
 
         let volume: &[Volume] = info.volume.borrow();
         let volume = (volume[0].0 as f64 / Volume::NORMAL.0 as f64) * 100.0;
 
-        self.channel_scale().scale().set_value(volume);
+        self.channel_scale().set_volume(volume);
 
         let running = info.state == SinkState::Running;
 
@@ -152,6 +169,15 @@ impl SinkItem {
         }
     }
 
+    pub fn name(&self) -> Option<String> {
+        self.imp().name.borrow().clone()
+    }
+
+    /// Marks the sink as the one new streams play on.
+    pub fn set_default(&self, default: bool) {
+        self.imp().default_image.set_visible(default);
+    }
+
     pub fn channel_scale(&self) -> &crate::widgets::ChannelScale {
         &self.imp().channel_scale
     }
@@ -160,6 +186,17 @@ impl SinkItem {
         &self.imp().level_box
     }
 
+    pub fn init_spectrum(
+        &self,
+        context: pulse_async::context::Context,
+        id: u32,
+        stream_id: Option<u32>,
+    ) {
+        let imp = self.imp();
+        imp.spectrum_view
+            .bind_popover(&imp.spectrum_popover, context, id, stream_id);
+    }
+
     pub fn set_title(&self, title: &str) {
         *self.imp().title.borrow_mut() = title.to_string();
         self.notify("title");
@@ -177,8 +214,50 @@ impl SinkItem {
 
     pub fn connect_volume_changed<F>(&self, cb: F)
     where
-        F: Fn(&gtk::Scale, Box<dyn FnOnce()>) + 'static,
+        F: Fn(&gtk::Scale) + 'static,
     {
         self.imp().channel_scale.get().connect_volume_changed(cb);
     }
+
+    pub fn connect_test<F>(&self, cb: F)
+    where
+        F: Fn(Signal) + 'static,
+    {
+        let cb = Rc::new(cb);
+        let imp = self.imp();
+
+        let buttons = [
+            (imp.test_sine_button.get(), Signal::Sine { freq: 440.0 }),
+            (
+                imp.test_sweep_button.get(),
+                Signal::Sweep {
+                    from: 20.0,
+                    to: 20000.0,
+                    duration: Duration::from_secs(2),
+                },
+            ),
+            (imp.test_noise_button.get(), Signal::PinkNoise),
+        ];
+
+        for (button, signal) in buttons {
+            let cb = cb.clone();
+            button.connect_clicked(move |_| cb(signal));
+        }
+    }
+
+    /// Shows which channel is being tested, `None` once the test is over.
+    pub fn set_test_channel(&self, channel: Option<&str>) {
+        let imp = self.imp();
+
+        for button in [
+            &imp.test_sine_button,
+            &imp.test_sweep_button,
+            &imp.test_noise_button,
+        ] {
+            button.set_sensitive(channel.is_none());
+        }
+
+        imp.test_label.set_label(channel.unwrap_or(""));
+        imp.test_label.set_visible(channel.is_some());
+    }
 }
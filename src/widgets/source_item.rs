@@ -0,0 +1,232 @@
+use adw::subclass::prelude::*;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+
+use pulse::{def::SourceState, volume::Volume};
+use pulse_async::SourceInfo;
+
+use gtk::CompositeTemplate;
+use std::{
+    borrow::Borrow,
+    cell::{Cell, RefCell},
+};
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(file = "source_item.ui")]
+    pub struct SourceItem {
+        #[template_child]
+        pub level_box: TemplateChild<crate::widgets::LevelBox>,
+        #[template_child]
+        pub title_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub subtitle_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub default_image: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub port_dropdown: TemplateChild<gtk::DropDown>,
+        #[template_child]
+        pub mute_button: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub channel_scale: TemplateChild<crate::widgets::ChannelScale>,
+
+        /// Name of the source, to tell whether it is the default one.
+        pub name: RefCell<Option<String>>,
+        pub monitor: Cell<bool>,
+        /// Port names, in the order of `port_dropdown`.
+        pub ports: RefCell<Vec<String>>,
+        /// Set while the buttons show server state, so it is not sent back.
+        pub updating: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SourceItem {
+        const NAME: &'static str = "SourceItem";
+        type Type = super::SourceItem;
+        type ParentType = adw::Bin;
+        type Interfaces = (gtk::Buildable,);
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for SourceItem {}
+    impl WidgetImpl for SourceItem {}
+    impl BuildableImpl for SourceItem {}
+    impl BinImpl for SourceItem {}
+}
+
+glib::wrapper! {
+    pub struct SourceItem(ObjectSubclass<imp::SourceItem>) @extends gtk::Widget;
+}
+
+impl SourceItem {
+    pub fn new() -> Self {
+        glib::Object::new(&[]).expect("Failed to create SourceItem")
+    }
+
+    pub fn update(&self, info: &SourceInfo) {
+        let imp = self.imp();
+
+        let title = info
+            .active_port
+            .as_ref()
+            .and_then(|port| port.description.as_deref())
+            .or(info.description.as_deref())
+            .unwrap_or("Unknown");
+        imp.title_label.set_label(&glib::markup_escape_text(title));
+        imp.subtitle_label.set_label(&glib::markup_escape_text(
+            info.description.as_deref().unwrap_or("Unknown"),
+        ));
+
+        let icon = if info.monitor_of_sink.is_some() {
+            "audio-speakers-symbolic"
+        } else {
+            "audio-input-microphone-symbolic"
+        };
+        imp.level_box.set_icon(icon);
+
+        *imp.name.borrow_mut() = info.name.clone();
+        imp.monitor.set(info.monitor_of_sink.is_some());
+
+        let volume: &[Volume] = info.volume.borrow();
+        if let Some(volume) = volume.first() {
+            self.channel_scale()
+                .set_volume((volume.0 as f64 / Volume::NORMAL.0 as f64) * 100.0);
+        }
+
+        imp.updating.set(true);
+        imp.mute_button.set_active(info.mute);
+        self.update_ports(info);
+        imp.updating.set(false);
+
+        imp.mute_button.set_icon_name(if info.mute {
+            "microphone-sensitivity-muted-symbolic"
+        } else {
+            "microphone-sensitivity-high-symbolic"
+        });
+
+        if info.state == SourceState::Running && !info.mute {
+            self.channel_scale()
+                .scale()
+                .style_context()
+                .remove_class("inactive");
+        } else {
+            self.channel_scale()
+                .scale()
+                .style_context()
+                .add_class("inactive");
+        }
+    }
+
+    fn update_ports(&self, info: &SourceInfo) {
+        let imp = self.imp();
+
+        let names: Vec<String> = info
+            .ports
+            .iter()
+            .map(|port| port.name.clone().unwrap_or_default())
+            .collect();
+
+        if *imp.ports.borrow() != names {
+            let descriptions: Vec<&str> = info
+                .ports
+                .iter()
+                .map(|port| {
+                    port.description
+                        .as_deref()
+                        .or(port.name.as_deref())
+                        .unwrap_or("Unknown")
+                })
+                .collect();
+
+            imp.port_dropdown
+                .set_model(Some(&gtk::StringList::new(&descriptions)));
+            *imp.ports.borrow_mut() = names;
+        }
+
+        let active = info.active_port.as_ref().and_then(|active| {
+            imp.ports
+                .borrow()
+                .iter()
+                .position(|name| Some(name) == active.name.as_ref())
+        });
+
+        if let Some(active) = active {
+            imp.port_dropdown.set_selected(active as u32);
+        }
+
+        // Nothing to choose from
+        imp.port_dropdown.set_visible(imp.ports.borrow().len() > 1);
+    }
+
+    pub fn name(&self) -> Option<String> {
+        self.imp().name.borrow().clone()
+    }
+
+    /// Whether the source records what a sink plays.
+    pub fn is_monitor(&self) -> bool {
+        self.imp().monitor.get()
+    }
+
+    /// Marks the source as the one new recordings use.
+    pub fn set_default(&self, default: bool) {
+        self.imp().default_image.set_visible(default);
+    }
+
+    pub fn channel_scale(&self) -> &crate::widgets::ChannelScale {
+        &self.imp().channel_scale
+    }
+
+    pub fn level_box(&self) -> &crate::widgets::LevelBox {
+        &self.imp().level_box
+    }
+
+    /// Calls `cb` with the new state when the user toggles mute.
+    pub fn connect_mute_toggled<F>(&self, cb: F)
+    where
+        F: Fn(bool) + 'static,
+    {
+        let this = self.downgrade();
+        self.imp().mute_button.connect_toggled(move |button| {
+            let updating = this
+                .upgrade()
+                .map_or(false, |this| this.imp().updating.get());
+
+            if !updating {
+                cb(button.is_active())
+            }
+        });
+    }
+
+    /// Calls `cb` with the name of the port the user picked.
+    pub fn connect_port_selected<F>(&self, cb: F)
+    where
+        F: Fn(&str) + 'static,
+    {
+        let this = self.downgrade();
+        self.imp()
+            .port_dropdown
+            .connect_selected_notify(move |dropdown| {
+                let this = match this.upgrade() {
+                    Some(this) => this,
+                    None => return,
+                };
+
+                if this.imp().updating.get() {
+                    return;
+                }
+
+                if let Some(port) = this.imp().ports.borrow().get(dropdown.selected() as usize) {
+                    cb(port);
+                }
+            });
+    }
+}
@@ -0,0 +1,171 @@
+use adw::subclass::prelude::*;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+
+use pulse::volume::Volume;
+use pulse_async::{AppMetadata, SourceOutputInfo};
+
+use gtk::CompositeTemplate;
+use once_cell::unsync::OnceCell;
+use std::{borrow::Borrow, cell::Cell};
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(file = "source_output_item.ui")]
+    pub struct SourceOutputItem {
+        #[template_child]
+        pub level_box: TemplateChild<crate::widgets::LevelBox>,
+        #[template_child]
+        pub title_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub subtitle_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub mute_button: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub channel_scale: TemplateChild<crate::widgets::ChannelScale>,
+
+        /// Set while the mute button shows server state, so it is not sent back.
+        pub updating: Cell<bool>,
+        /// Context and index of the source the level meter records.
+        pub meter_context: OnceCell<pulse_async::context::Context>,
+        pub meter_source: Cell<u32>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SourceOutputItem {
+        const NAME: &'static str = "SourceOutputItem";
+        type Type = super::SourceOutputItem;
+        type ParentType = adw::Bin;
+        type Interfaces = (gtk::Buildable,);
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for SourceOutputItem {}
+    impl WidgetImpl for SourceOutputItem {}
+    impl BuildableImpl for SourceOutputItem {}
+    impl BinImpl for SourceOutputItem {}
+}
+
+glib::wrapper! {
+    pub struct SourceOutputItem(ObjectSubclass<imp::SourceOutputItem>) @extends gtk::Widget;
+}
+
+impl SourceOutputItem {
+    pub fn new() -> Self {
+        glib::Object::new(&[]).expect("Failed to create SourceOutputItem")
+    }
+
+    pub fn update(&self, info: &SourceOutputInfo) {
+        let imp = self.imp();
+        let app = AppMetadata::from(&info.proplist);
+
+        imp.title_label.set_label(&glib::markup_escape_text(
+            app.name.as_deref().unwrap_or("Unknown"),
+        ));
+        imp.subtitle_label.set_label(&glib::markup_escape_text(
+            info.name.as_deref().unwrap_or_default(),
+        ));
+
+        let theme = gtk::IconTheme::default();
+        let icon_name = app
+            .icon_candidates()
+            .into_iter()
+            .find(|icon| theme.has_icon(icon));
+
+        if let Some(icon_name) = icon_name {
+            imp.level_box.set_icon(&icon_name);
+        }
+
+        // The output was moved to another source
+        if let Some(context) = imp.meter_context.get() {
+            if imp.meter_source.get() != info.source {
+                imp.meter_source.set(info.source);
+                imp.level_box
+                    .init_stream(context.clone(), info.source, None);
+            }
+        }
+
+        let volume: &[Volume] = info.volume.borrow();
+        if let Some(volume) = volume.first() {
+            self.channel_scale()
+                .set_volume((volume.0 as f64 / Volume::NORMAL.0 as f64) * 100.0);
+        }
+        self.channel_scale()
+            .set_sensitive(info.has_volume && info.volume_writable);
+
+        imp.updating.set(true);
+        imp.mute_button.set_active(info.mute);
+        imp.updating.set(false);
+
+        imp.mute_button.set_icon_name(if info.mute {
+            "microphone-sensitivity-muted-symbolic"
+        } else {
+            "microphone-sensitivity-high-symbolic"
+        });
+
+        if !info.corked && !info.mute {
+            self.channel_scale()
+                .scale()
+                .style_context()
+                .remove_class("inactive");
+        } else {
+            self.channel_scale()
+                .scale()
+                .style_context()
+                .add_class("inactive");
+        }
+    }
+
+    pub fn channel_scale(&self) -> &crate::widgets::ChannelScale {
+        &self.imp().channel_scale
+    }
+
+    pub fn level_box(&self) -> &crate::widgets::LevelBox {
+        &self.imp().level_box
+    }
+
+    /// Meters `source`, the device the application records from.
+    ///
+    /// PulseAudio can't monitor a source output by itself, so the meter
+    /// shows the whole device and follows the output when it is moved.
+    pub fn init_meter(&self, context: pulse_async::context::Context, source: u32) {
+        let imp = self.imp();
+
+        imp.meter_source.set(source);
+        imp.level_box.init_stream(context.clone(), source, None);
+        imp.meter_context.set(context).ok();
+    }
+
+    pub fn connect_volume_changed<F>(&self, cb: F)
+    where
+        F: Fn(&gtk::Scale) + 'static,
+    {
+        self.imp().channel_scale.get().connect_volume_changed(cb);
+    }
+
+    /// Calls `cb` with the new state when the user toggles mute.
+    pub fn connect_mute_toggled<F>(&self, cb: F)
+    where
+        F: Fn(bool) + 'static,
+    {
+        let this = self.downgrade();
+        self.imp().mute_button.connect_toggled(move |button| {
+            let updating = this
+                .upgrade()
+                .map_or(false, |this| this.imp().updating.get());
+
+            if !updating {
+                cb(button.is_active())
+            }
+        });
+    }
+}
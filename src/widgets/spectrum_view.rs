@@ -0,0 +1,162 @@
+use futures::pin_mut;
+use futures::StreamExt;
+use glib::clone;
+use glib::SourceId;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use std::cell::RefCell;
+
+use pulse_async::dsp::{SpectrumConfig, SPECTRUM_FLOOR_DB};
+
+mod imp {
+    use super::*;
+
+    #[derive(Default)]
+    pub struct SpectrumView {
+        pub bands: RefCell<Vec<f32>>,
+        pub stream: RefCell<Option<SourceId>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SpectrumView {
+        const NAME: &'static str = "SpectrumView";
+        type Type = super::SpectrumView;
+        type ParentType = gtk::DrawingArea;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.set_css_name("spectrumview");
+        }
+    }
+
+    impl ObjectImpl for SpectrumView {
+        fn constructed(&self, obj: &Self::Type) {
+            self.parent_constructed(obj);
+
+            obj.set_draw_func(|area, cr, width, height| {
+                if let Some(view) = area.downcast_ref::<super::SpectrumView>() {
+                    view.draw(cr, width, height);
+                }
+            });
+        }
+
+        fn dispose(&self, obj: &Self::Type) {
+            obj.stop_stream();
+        }
+    }
+
+    impl WidgetImpl for SpectrumView {}
+    impl DrawingAreaImpl for SpectrumView {}
+}
+
+glib::wrapper! {
+    pub struct SpectrumView(ObjectSubclass<imp::SpectrumView>)
+        @extends gtk::DrawingArea, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl SpectrumView {
+    pub fn new() -> Self {
+        glib::Object::new(&[]).expect("Failed to create SpectrumView")
+    }
+
+    pub fn set_bands(&self, bands: Vec<f32>) {
+        {
+            let mut current = self.imp().bands.borrow_mut();
+
+            if current.len() != bands.len() {
+                *current = bands;
+            } else {
+                // Let bars fall slowly, same idea as the decay in LevelBox
+                const DECAY_STEP: f32 = 1.5;
+                for (old, new) in current.iter_mut().zip(bands) {
+                    *old = new.max(*old - DECAY_STEP);
+                }
+            }
+        }
+
+        self.queue_draw();
+    }
+
+    pub fn init_stream(
+        &self,
+        context: pulse_async::context::Context,
+        id: u32,
+        stream_id: Option<u32>,
+    ) {
+        self.stop_stream();
+
+        let view = self.downgrade();
+        let source_id = glib::MainContext::default().spawn_local(async move {
+            let stream = context.crate_spectrum_stream(id, stream_id, SpectrumConfig::default());
+
+            pin_mut!(stream);
+
+            while let Some(bands) = stream.next().await {
+                if let Some(view) = view.upgrade() {
+                    view.set_bands(bands);
+                } else {
+                    break;
+                }
+            }
+        });
+
+        self.imp().stream.replace(Some(source_id));
+    }
+
+    /// Records `id` while `popover` is shown, so the spectrum only costs while it is visible.
+    pub fn bind_popover(
+        &self,
+        popover: &gtk::Popover,
+        context: pulse_async::context::Context,
+        id: u32,
+        stream_id: Option<u32>,
+    ) {
+        popover.connect_show(clone!(@weak self as view => move |_| {
+            view.init_stream(context.clone(), id, stream_id);
+        }));
+
+        popover.connect_closed(clone!(@weak self as view => move |_| {
+            view.stop_stream();
+        }));
+    }
+
+    pub fn stop_stream(&self) {
+        if let Some(id) = self.imp().stream.borrow_mut().take() {
+            id.remove();
+        }
+
+        self.imp().bands.borrow_mut().clear();
+        self.queue_draw();
+    }
+
+    fn draw(&self, cr: &gtk::cairo::Context, width: i32, height: i32) {
+        let bands = self.imp().bands.borrow();
+
+        if bands.is_empty() {
+            return;
+        }
+
+        let color = self.style_context().color();
+        cr.set_source_rgba(
+            color.red() as f64,
+            color.green() as f64,
+            color.blue() as f64,
+            color.alpha() as f64,
+        );
+
+        let width = width as f64;
+        let height = height as f64;
+
+        let slot = width / bands.len() as f64;
+        let gap = (slot * 0.2).min(4.0);
+
+        for (i, db) in bands.iter().enumerate() {
+            let level = ((db - SPECTRUM_FLOOR_DB) / -SPECTRUM_FLOOR_DB).clamp(0.0, 1.0) as f64;
+            let bar = level * height;
+
+            cr.rectangle(i as f64 * slot + gap / 2.0, height - bar, slot - gap, bar);
+        }
+
+        cr.fill().ok();
+    }
+}
@@ -1,10 +1,21 @@
+use std::time::Duration;
+
+use futures::future::AbortHandle;
+use glib::clone;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use gtk::{gio, glib, CompositeTemplate};
 
+use pulse_async::{FadeCurve, VolumeLevel};
+
+use crate::pages::ServerPage;
+
+/// How long the sleep timer takes to fade the default output to silence.
+const SLEEP_FADE: Duration = Duration::from_secs(30);
+
 mod imp {
     use adw::subclass::prelude::AdwApplicationWindowImpl;
-    use once_cell::unsync::OnceCell;
+    use std::cell::RefCell;
 
     use super::*;
 
@@ -12,12 +23,25 @@ mod imp {
     #[template(resource = "/com/github/polymeilex/Cathode/window.ui")]
     pub struct CathodeWindow {
         #[template_child]
-        pub playback_page: TemplateChild<crate::pages::PlaybackPage>,
+        pub servers_stack: TemplateChild<gtk::Stack>,
 
         #[template_child]
-        pub output_page: TemplateChild<crate::pages::OutputPage>,
+        pub server_dropdown: TemplateChild<gtk::DropDown>,
 
-        pub context: OnceCell<pulse_async::Context>,
+        #[template_child]
+        pub server_list: TemplateChild<gtk::StringList>,
+
+        #[template_child]
+        pub switcher_title: TemplateChild<adw::ViewSwitcherTitle>,
+
+        #[template_child]
+        pub switcher_bar: TemplateChild<adw::ViewSwitcherBar>,
+
+        /// One page per connected server with the address it was added with, in the same
+        /// order as `server_list`.
+        pub servers: RefCell<Vec<(Option<String>, ServerPage)>>,
+
+        pub sleep_timer: RefCell<Option<AbortHandle>>,
     }
 
     #[glib::object_subclass]
@@ -28,6 +52,32 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             Self::bind_template(klass);
+
+            klass.install_action("win.measure-latency", None, |win, _, _| {
+                win.show_latency_dialog();
+            });
+
+            klass.install_action("win.show-diagnostics", None, |win, _, _| {
+                win.show_diagnostics_dialog();
+            });
+
+            klass.install_action("win.connect-server", None, |win, _, _| {
+                win.show_server_dialog();
+            });
+
+            klass.install_action("win.disconnect-server", None, |win, _, _| {
+                win.remove_current_server();
+            });
+
+            klass.install_action("win.sleep-timer", Some("u"), |win, _, minutes| {
+                if let Some(minutes) = minutes.and_then(|minutes| minutes.get::<u32>()) {
+                    win.start_sleep_timer(Duration::from_secs(minutes as u64 * 60));
+                }
+            });
+
+            klass.install_action("win.cancel-sleep-timer", None, |win, _, _| {
+                win.cancel_sleep_timer();
+            });
         }
 
         fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
@@ -35,7 +85,18 @@ mod imp {
         }
     }
 
-    impl ObjectImpl for CathodeWindow {}
+    impl ObjectImpl for CathodeWindow {
+        fn constructed(&self, obj: &Self::Type) {
+            self.parent_constructed(obj);
+
+            self.server_dropdown
+                .connect_selected_notify(clone!(@weak obj => move |dropdown| {
+                    obj.show_server(dropdown.selected());
+                }));
+
+            obj.action_set_enabled("win.disconnect-server", false);
+        }
+    }
     impl WidgetImpl for CathodeWindow {}
     impl WindowImpl for CathodeWindow {}
     impl ApplicationWindowImpl for CathodeWindow {}
@@ -53,19 +114,189 @@ impl CathodeWindow {
         glib::Object::new(&[("application", application)]).expect("Failed to create CathodeWindow")
     }
 
-    pub fn init_context(&self, context: pulse_async::Context) {
-        self.imp().context.set(context).unwrap()
+    /// Adds a page for `server` (or the default server if `None`), connects to it and shows it.
+    ///
+    /// A server that already has a page is only shown again.
+    pub fn add_server(&self, server: Option<String>) -> ServerPage {
+        let imp = self.imp();
+
+        let existing = imp
+            .servers
+            .borrow()
+            .iter()
+            .position(|(address, _)| *address == server);
+
+        if let Some(position) = existing {
+            imp.server_dropdown.set_selected(position as u32);
+            self.show_server(position as u32);
+
+            return imp.servers.borrow()[position].1.clone();
+        }
+
+        let page = ServerPage::new();
+        imp.servers_stack.add_child(&page);
+
+        let position = {
+            let mut servers = imp.servers.borrow_mut();
+            servers.push((server.clone(), page.clone()));
+            servers.len() as u32 - 1
+        };
+
+        page.connect_notify_local(
+            Some("server"),
+            clone!(@weak self as win => move |page, _| {
+                win.server_changed(page);
+            }),
+        );
+
+        imp.server_list
+            .append(server.as_deref().unwrap_or("Default Server"));
+        imp.server_dropdown.set_visible(position > 0);
+        self.action_set_enabled("win.disconnect-server", position > 0);
+
+        crate::run::run(self.clone(), page.clone(), server);
+
+        imp.server_dropdown.set_selected(position);
+        // Selecting the first entry does not notify
+        self.show_server(position);
+
+        page
+    }
+
+    fn server_changed(&self, page: &ServerPage) {
+        if self.current_server().as_ref() == Some(page) {
+            self.imp().switcher_title.set_subtitle(Some(&page.server()));
+        }
     }
 
-    pub fn context(&self) -> &pulse_async::Context {
-        self.imp().context.get().unwrap()
+    fn show_server(&self, position: u32) {
+        let imp = self.imp();
+
+        let page = match imp.servers.borrow().get(position as usize) {
+            Some((_, page)) => page.clone(),
+            None => return,
+        };
+
+        imp.servers_stack.set_visible_child(&page);
+        imp.switcher_title.set_stack(Some(page.stack()));
+        imp.switcher_bar.set_stack(Some(page.stack()));
+        imp.switcher_title.set_subtitle(Some(&page.server()));
     }
 
-    pub fn playback_page(&self) -> &crate::pages::PlaybackPage {
-        &self.imp().playback_page
+    /// Page of the server currently shown.
+    pub fn current_server(&self) -> Option<ServerPage> {
+        let imp = self.imp();
+
+        imp.servers
+            .borrow()
+            .get(imp.server_dropdown.selected() as usize)
+            .map(|(_, page)| page.clone())
+    }
+
+    /// Disconnects from the server currently shown and drops its page.
+    ///
+    /// The last server is kept, the window would be empty otherwise.
+    pub fn remove_current_server(&self) {
+        let imp = self.imp();
+        let position = imp.server_dropdown.selected();
+
+        let page = {
+            let mut servers = imp.servers.borrow_mut();
+
+            if servers.len() < 2 || position as usize >= servers.len() {
+                return;
+            }
+
+            servers.remove(position as usize).1
+        };
+
+        imp.servers_stack.remove(&page);
+        imp.server_list.remove(position);
+
+        let remaining = imp.servers.borrow().len() as u32;
+        imp.server_dropdown.set_visible(remaining > 1);
+        self.action_set_enabled("win.disconnect-server", remaining > 1);
+
+        let position = position.min(remaining - 1);
+        imp.server_dropdown.set_selected(position);
+        self.show_server(position);
+
+        page.disconnect();
+    }
+
+    pub fn show_latency_dialog(&self) {
+        if let Some(page) = self.current_server() {
+            let dialog = crate::widgets::LatencyDialog::new(self, page.context().clone());
+            dialog.present();
+        }
     }
 
-    pub fn output_page(&self) -> &crate::pages::OutputPage {
-        &self.imp().output_page
+    pub fn show_diagnostics_dialog(&self) {
+        if let Some(page) = self.current_server() {
+            let dialog = crate::widgets::DiagnosticsDialog::new(self, page.context().clone());
+            dialog.present();
+        }
+    }
+
+    pub fn show_server_dialog(&self) {
+        let app = match self
+            .application()
+            .and_then(|app| app.downcast::<crate::CathodeApplication>().ok())
+        {
+            Some(app) => app,
+            None => return,
+        };
+
+        let dialog = crate::widgets::ServerDialog::new(self, &app.recent_servers());
+        dialog.connect_server_selected(clone!(@weak self as win, @weak app => move |server| {
+            app.add_recent_server(server);
+            win.add_server(Some(server.to_string()));
+        }));
+        dialog.present();
+    }
+
+    /// Fades the default output of the current server to silence once `after` has elapsed.
+    pub fn start_sleep_timer(&self, after: Duration) {
+        self.cancel_sleep_timer();
+
+        let context = match self.current_server() {
+            Some(page) => page.context().clone(),
+            None => return,
+        };
+
+        let (timer, handle) = futures::future::abortable(async move {
+            context.sleep(after).await;
+
+            let default_sink = match context.introspect().server_info().await {
+                Ok(info) => info.default_sink_name,
+                Err(_) => return,
+            };
+
+            let sinks = context.introspect().sink_list().await.unwrap_or_default();
+
+            if let Some(sink) = sinks.iter().find(|sink| sink.name == default_sink) {
+                context
+                    .fade_sink_volume(
+                        sink.index,
+                        VolumeLevel::MUTED,
+                        SLEEP_FADE,
+                        FadeCurve::Logarithmic,
+                    )
+                    .await
+                    .ok();
+            }
+        });
+
+        *self.imp().sleep_timer.borrow_mut() = Some(handle);
+
+        glib::MainContext::default().spawn_local(async move {
+            timer.await.ok();
+        });
+    }
+
+    pub fn cancel_sleep_timer(&self) {
+        if let Some(handle) = self.imp().sleep_timer.take() {
+            handle.abort();
+        }
     }
 }